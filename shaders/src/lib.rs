@@ -60,10 +60,30 @@ pub struct ShaderPipeline {
 
 #[derive(Debug)]
 pub enum Error {
+    /// Slang failed, e.g. with `slang::Error::Compile` and its diagnostics when the shader has errors.
     SlangError(slang::Error),
+    /// The shader compiled, but Slang reported warnings.
     CompilerOutput(String),
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::SlangError(_) => write!(f, "failed to compile shaders"),
+            Error::CompilerOutput(output) => write!(f, "{}", output.trim_end()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::SlangError(error) => Some(error),
+            Error::CompilerOutput(_) => None,
+        }
+    }
+}
+
 impl std::convert::From<slang::Error> for Error {
     fn from(t: slang::Error) -> Self {
         Error::SlangError(t)
@@ -78,70 +98,76 @@ impl ShaderPipeline {
         pixel_entry_point_name: &str,
     ) -> Result<ShaderPipeline, Error> {
         struct BackendSelector {
-            profile: &'static str,
+            profile: slang::Profile,
             target: slang::CompileTarget,
             backend: Backend,
         }
 
         #[cfg(windows)]
         let selector = BackendSelector {
-            profile: "sm_4_0",
+            profile: slang::Profile::Sm(4, 0),
             target: slang::CompileTarget::Dxbc,
             backend: Backend::HlslSm40,
         };
 
+        // Slang gives varyings explicit `layout(location = ...)`s, which GLSL only has from 3.30, so a `glsl_150`
+        // profile produces code that doesn't compile. Use 4.50, the version Slang's GLSL output is written for.
         #[cfg(not(windows))]
         let selector = BackendSelector {
-            profile: "glsl_150",                // TODO this isn't working?
+            profile: slang::Profile::Glsl(450),
             target: slang::CompileTarget::Glsl, // spirv?
             backend: Backend::Glsl150,
         };
 
+        if !selector.profile.is_compatible_with(selector.target) {
+            return Err(
+                slang::Error::Builder(slang::BuilderError::IncompatibleProfile {
+                    profile: selector.profile,
+                    target: selector.target,
+                })
+                .into(),
+            );
+        }
+
         let session = slang::Session::new();
-        let profile_id = session
-            .find_profile(std::ffi::CString::new(selector.profile).unwrap())
-            .expect("profile is non-zero");
+        let profile_id = session.find_profile(&selector.profile)?.ok_or_else(|| {
+            slang::Error::Builder(slang::BuilderError::UnknownProfile(
+                selector.profile.to_string(),
+            ))
+        })?;
 
-        let request = session.create_compile_request();
+        let mut request = session.create_compile_request();
 
         let code_gen_target = request.add_code_gen_target(selector.target);
 
-        request.set_target_profile(code_gen_target, profile_id);
+        request.set_target_profile(code_gen_target, profile_id)?;
 
-        let translation_unit = request.add_translation_unit(
-            slang::SourceLanguage::Slang,
-            std::ffi::CString::new("shader").unwrap(),
-        );
+        let translation_unit =
+            request.add_translation_unit(slang::SourceLanguage::Slang, "shader")?;
 
-        request.add_translation_unit_source_string(
-            translation_unit,
-            std::ffi::CString::new(file_path).unwrap(),
-            std::ffi::CString::new(shader_source).unwrap(),
-        );
+        request.add_translation_unit_source_string(translation_unit, file_path, shader_source)?;
 
         let entry_point_vertex = request.add_entry_point(
             translation_unit,
-            std::ffi::CString::new(vertex_entry_point_name).unwrap(),
+            vertex_entry_point_name,
             slang::Stage::Vertex,
-        );
+        )?;
 
         let entry_point_pixel = request.add_entry_point(
             translation_unit,
-            std::ffi::CString::new(pixel_entry_point_name).unwrap(),
+            pixel_entry_point_name,
             slang::Stage::Fragment,
-        );
+        )?;
 
-        let res = request.compile();
+        request.compile()?;
 
+        // A successful compile can still have diagnostic output.
         let diagnostic_output = request.get_diagnostic_output();
         if diagnostic_output.to_bytes().len() > 1 {
             Err(Error::CompilerOutput(
                 diagnostic_output.to_string_lossy().to_string(),
             ))
         } else {
-            // I believe we can suceed a compile and still get diagnostic output
-            res?;
-
             #[cfg(target_os = "emscripten")]
             {
                 // TODO: convert to 300es
@@ -174,8 +200,8 @@ impl ShaderPipeline {
 
             Ok(ShaderPipeline {
                 backend: selector.backend,
-                vertex: request.get_entry_point_code(entry_point_vertex).to_vec(),
-                pixel: request.get_entry_point_code(entry_point_pixel).to_vec(),
+                vertex: request.get_entry_point_code(entry_point_vertex)?.to_vec(),
+                pixel: request.get_entry_point_code(entry_point_pixel)?.to_vec(),
             })
         }
     }
@@ -0,0 +1,325 @@
+//! Compiles Slang shaders from a build script.
+//!
+//! In `build.rs`:
+//!
+//! ```no_run
+//! use slang::{CompileTarget, Stage, TargetDesc};
+//!
+//! slang_build::Build::new()
+//!     .target("spirv", TargetDesc::new(CompileTarget::Spirv).profile("glsl_450"))
+//!     .entry_point("shaders/blur.slang", "main", Stage::Compute)
+//!     .define("RADIUS", "4")
+//!     .compile("shaders");
+//! ```
+//!
+//! Then in the crate:
+//!
+//! ```ignore
+//! mod shaders {
+//!     include!(concat!(env!("OUT_DIR"), "/shaders.rs"));
+//! }
+//!
+//! let code: &[u8] = shaders::BLUR_MAIN_SPIRV;
+//! ```
+//!
+//! Each output is written to `OUT_DIR` as `<file stem>_<entry point>.<target name>`, and the generated module has
+//! one `&[u8]` constant per output named `<FILE STEM>_<ENTRY POINT>_<TARGET NAME>`, which `include_bytes!`s it.
+
+#![warn(clippy::all)]
+
+use slang::{
+    CompileRequestBuilder, Session, SourceLanguage, Stage, TargetDesc, TranslationUnitDesc,
+};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    Slang(slang::Error),
+    Io(io::Error),
+    /// `OUT_DIR` isn't set and no directory was given with `Build::out_dir`.
+    MissingOutDir,
+    /// Two outputs would get the same constant name.
+    DuplicateOutput(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Slang(error) => write!(f, "{}", error),
+            Error::Io(error) => write!(f, "{}", error),
+            Error::MissingOutDir => write!(f, "OUT_DIR is not set"),
+            Error::DuplicateOutput(name) => write!(f, "more than one output is named {}", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<slang::Error> for Error {
+    fn from(error: slang::Error) -> Self {
+        Error::Slang(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EntryPoint {
+    file: PathBuf,
+    name: String,
+    stage: Stage,
+}
+
+/// Collects shader files, entry points and targets, then compiles them all with `compile`.
+#[derive(Clone, Debug, Default)]
+pub struct Build {
+    targets: Vec<(String, TargetDesc)>,
+    entry_points: Vec<EntryPoint>,
+    defines: Vec<(String, String)>,
+    search_paths: Vec<PathBuf>,
+    out_dir: Option<PathBuf>,
+    emit_rerun_if_changed: Option<bool>,
+}
+
+impl Build {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every entry point is compiled for every target. `name` is used as the output's file extension and
+    /// constant suffix, e.g. `"spirv"` or `"dxil"`.
+    pub fn target(&mut self, name: impl Into<String>, desc: TargetDesc) -> &mut Self {
+        self.targets.push((name.into(), desc));
+        self
+    }
+
+    /// Compiles the entry point `name` from the Slang file at `file`. Relative paths are relative to the package
+    /// root, like other paths in build scripts.
+    pub fn entry_point(
+        &mut self,
+        file: impl Into<PathBuf>,
+        name: impl Into<String>,
+        stage: Stage,
+    ) -> &mut Self {
+        self.entry_points.push(EntryPoint {
+            file: file.into(),
+            name: name.into(),
+            stage,
+        });
+        self
+    }
+
+    /// A preprocessor define for every file.
+    pub fn define(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.defines.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn search_path(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// Overrides the output directory, which defaults to `OUT_DIR`.
+    pub fn out_dir(&mut self, out_dir: impl Into<PathBuf>) -> &mut Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// Whether to print `cargo:rerun-if-changed` for every file the shaders depend on. Defaults to true when
+    /// running in a build script, i.e. when `OUT_DIR` is set.
+    pub fn emit_rerun_if_changed(&mut self, emit: bool) -> &mut Self {
+        self.emit_rerun_if_changed = Some(emit);
+        self
+    }
+
+    /// Like `try_compile`, but panics with the error, as build scripts usually want.
+    pub fn compile(&self, module_name: &str) {
+        if let Err(error) = self.try_compile(module_name) {
+            panic!("failed to compile shaders: {}", error);
+        }
+    }
+
+    /// Compiles every entry point for every target, writes the outputs and `<module_name>.rs` to the output
+    /// directory, and returns the path of the generated module.
+    pub fn try_compile(&self, module_name: &str) -> Result<PathBuf, Error> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(Error::MissingOutDir)?,
+        };
+        let emit_rerun_if_changed = self
+            .emit_rerun_if_changed
+            .unwrap_or_else(|| env::var_os("OUT_DIR").is_some());
+
+        let builder = self.request_builder();
+        let output = builder.compile(&Session::new());
+
+        if emit_rerun_if_changed {
+            // Even a failed compile should be retried when the shaders are edited.
+            for entry_point in &self.entry_points {
+                println!("cargo:rerun-if-changed={}", entry_point.file.display());
+            }
+        }
+
+        let output = output?;
+        if emit_rerun_if_changed {
+            for path in &output.dependencies {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+
+        // Name every output before writing any, so a clash doesn't leave some of them behind.
+        let mut outputs = Vec::new();
+        for (entry_point, entry_point_output) in self.entry_points.iter().zip(&output.entry_points)
+        {
+            let stem = entry_point
+                .file
+                .file_stem()
+                .map_or_else(|| "shader".into(), |stem| stem.to_string_lossy());
+
+            for ((target_name, _), code) in self.targets.iter().zip(&entry_point_output.code) {
+                let constant_name = constant_name(&[&stem, &entry_point.name, target_name]);
+                if outputs.iter().any(|(name, _, _)| *name == constant_name) {
+                    return Err(Error::DuplicateOutput(constant_name));
+                }
+
+                let file_name = format!("{}_{}.{}", stem, entry_point.name, target_name);
+                outputs.push((constant_name, out_dir.join(file_name), code));
+            }
+        }
+
+        fs::create_dir_all(&out_dir)?;
+
+        let mut module = String::from("// Generated by slang-build.\n");
+        for (constant_name, path, code) in outputs {
+            fs::write(&path, code)?;
+            module.push_str(&format!(
+                "pub const {}: &[u8] = include_bytes!({});\n",
+                constant_name,
+                string_literal(&absolute(&path)?)?
+            ));
+        }
+
+        let module_path = out_dir.join(format!("{}.rs", module_name));
+        fs::write(&module_path, module)?;
+        Ok(module_path)
+    }
+
+    fn request_builder(&self) -> CompileRequestBuilder {
+        let mut builder = CompileRequestBuilder::new();
+        for (_, target) in &self.targets {
+            builder.add_target(target.clone());
+        }
+        for path in &self.search_paths {
+            builder.search_path(path.clone());
+        }
+        for (key, value) in &self.defines {
+            builder.define(key.clone(), value.clone());
+        }
+
+        // One translation unit per file, shared by all of its entry points.
+        let mut files: Vec<(&Path, slang::TranslationUnitId)> = Vec::new();
+        for entry_point in &self.entry_points {
+            let existing = files
+                .iter()
+                .find(|(file, _)| *file == entry_point.file.as_path())
+                .map(|&(_, unit)| unit);
+            let unit = match existing {
+                Some(unit) => unit,
+                None => {
+                    let unit = builder.add_translation_unit(
+                        TranslationUnitDesc::new(
+                            SourceLanguage::Slang,
+                            entry_point.file.to_string_lossy(),
+                        )
+                        .source_file(&entry_point.file),
+                    );
+                    files.push((&entry_point.file, unit));
+                    unit
+                }
+            };
+
+            builder.add_entry_point(unit, entry_point.name.clone(), entry_point.stage);
+        }
+
+        builder
+    }
+}
+
+/// e.g. `["blur", "main", "spirv"]` becomes `BLUR_MAIN_SPIRV`.
+fn constant_name(parts: &[&str]) -> String {
+    let mut name = parts
+        .join("_")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// A Rust string literal for `path`, which must be UTF-8 to be written in source.
+fn string_literal(path: &Path) -> Result<String, Error> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| slang::Error::NonUnicodePath(path.to_path_buf()))?;
+    Ok(format!("\"{}\"", path.escape_default()))
+}
+
+/// `include_bytes!` resolves relative paths against the including file, so the generated module uses absolute
+/// paths.
+fn absolute(path: &Path) -> io::Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(env::current_dir()?.join(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_names() {
+        assert_eq!(constant_name(&["blur", "main", "spirv"]), "BLUR_MAIN_SPIRV");
+        assert_eq!(
+            constant_name(&["post-fx", "vertexMain", "dxil"]),
+            "POST_FX_VERTEXMAIN_DXIL"
+        );
+        assert_eq!(constant_name(&["2d", "main", "spirv"]), "_2D_MAIN_SPIRV");
+        assert_eq!(
+            constant_name(&["tönemap", "main", "spirv"]),
+            "T_NEMAP_MAIN_SPIRV"
+        );
+    }
+
+    #[test]
+    fn string_literals_are_escaped() {
+        assert_eq!(
+            string_literal(Path::new("/out/blur_main.spirv")).unwrap(),
+            r#""/out/blur_main.spirv""#
+        );
+        assert_eq!(
+            string_literal(Path::new(r#"C:\out\"quoted"\é.spirv"#)).unwrap(),
+            r#""C:\\out\\\"quoted\"\\\u{e9}.spirv""#
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! `include_slang!` compiles a Slang shader while the crate using it is compiled.
+//!
+//! ```ignore
+//! use slang_macros::include_slang;
+//!
+//! // SPIR-V is embedded as words.
+//! static BLUR: &[u32] = include_slang!("shaders/blur.slang", entry = "main", stage = Compute, target = Spirv);
+//!
+//! // Everything else is embedded as bytes.
+//! static BLUR_DXIL: &[u8] = include_slang!(
+//!     "shaders/blur.slang",
+//!     entry = "main",
+//!     stage = Compute,
+//!     target = Dxil,
+//!     profile = "sm_6_0",
+//!     define = "RADIUS=4",
+//! );
+//! ```
+//!
+//! The path is relative to the root of the crate using the macro (`CARGO_MANIFEST_DIR`). `profile` is optional,
+//! and `define` can be given any number of times. Slang's diagnostics become compile errors at the macro call.
+
+#![warn(clippy::all)]
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use slang::{
+    CompileRequestBuilder, CompileTarget, Session, SourceLanguage, Stage, TargetDesc,
+    TranslationUnitDesc,
+};
+use std::path::PathBuf;
+
+#[proc_macro]
+pub fn include_slang(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(tokens) => tokens,
+        Err(error) => error.into_compile_error(),
+    }
+}
+
+struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// `compile_error!("message")`, with every token spanned to where the error is.
+    fn into_compile_error(self) -> TokenStream {
+        let mut message = Literal::string(&self.message);
+        message.set_span(self.span);
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(self.span);
+        let mut arguments = Group::new(Delimiter::Parenthesis, TokenTree::Literal(message).into());
+        arguments.set_span(self.span);
+
+        vec![
+            TokenTree::Ident(Ident::new("compile_error", self.span)),
+            TokenTree::Punct(bang),
+            TokenTree::Group(arguments),
+        ]
+        .into_iter()
+        .collect()
+    }
+}
+
+struct Args {
+    path: String,
+    entry: String,
+    stage: Stage,
+    target: CompileTarget,
+    profile: Option<String>,
+    defines: Vec<(String, String)>,
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream, Error> {
+    let args = parse_args(input)?;
+    let call_site = Span::call_site();
+
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
+        .ok_or_else(|| Error::new(call_site, "CARGO_MANIFEST_DIR is not set"))?;
+    let path = PathBuf::from(manifest_dir).join(&args.path);
+    let source = std::fs::read_to_string(&path).map_err(|error| {
+        Error::new(
+            call_site,
+            format!("couldn't read {}: {}", path.display(), error),
+        )
+    })?;
+
+    let mut target = TargetDesc::new(args.target);
+    if let Some(profile) = &args.profile {
+        target = target.profile(profile.clone());
+    }
+
+    let mut builder = CompileRequestBuilder::new();
+    let target = builder.add_target(target);
+    for (key, value) in &args.defines {
+        builder.define(key.clone(), value.clone());
+    }
+    let path_name = path.to_string_lossy();
+    let unit = builder.add_translation_unit(
+        TranslationUnitDesc::new(SourceLanguage::Slang, path_name.clone())
+            .source_string(path_name, source),
+    );
+    let entry_point = builder.add_entry_point(unit, args.entry.clone(), args.stage);
+
+    let output = builder.compile(&Session::new()).map_err(|error| {
+        let message = match error {
+            slang::Error::Compile(error) => format!("failed to compile {}:\n{}", args.path, error),
+            error => format!("failed to compile {}: {}", args.path, error),
+        };
+        Error::new(call_site, message)
+    })?;
+    let code = output
+        .code(entry_point, target)
+        .expect("ids are from the same builder");
+
+    // Proc macros can't tell cargo which files they read, but `include_bytes!` can, so every dependency is
+    // included (and ignored) to recompile when it changes.
+    // `include_bytes!` resolves relative paths against the file calling the macro, so they're made absolute
+    // first. Slang reports them relative to the directory it ran in, which is this process's.
+    let current_dir = std::env::current_dir().map_err(|error| {
+        Error::new(
+            call_site,
+            format!("couldn't get the current directory: {}", error),
+        )
+    })?;
+    let mut expansion = String::from("{");
+    for dependency in &output.dependencies {
+        let dependency = current_dir.join(dependency);
+        if dependency.is_file() {
+            let dependency = dependency.to_str().ok_or_else(|| {
+                Error::new(
+                    call_site,
+                    format!("dependency {} is not valid UTF-8", dependency.display()),
+                )
+            })?;
+            expansion.push_str(&format!(
+                "const _: &[u8] = include_bytes!({});",
+                Literal::string(dependency)
+            ));
+        }
+    }
+
+    if args.target == CompileTarget::Spirv {
+        if code.len() % 4 != 0 {
+            return Err(Error::new(
+                call_site,
+                "SPIR-V output is not a whole number of words",
+            ));
+        }
+        expansion.push_str("const CODE: &[u32] = &[");
+        for word in code.chunks_exact(4) {
+            let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+            expansion.push_str(&format!("{:#010x},", word));
+        }
+        expansion.push_str("]; CODE }");
+    } else {
+        expansion.push_str(&format!(
+            "const CODE: &[u8] = {}; CODE }}",
+            Literal::byte_string(code)
+        ));
+    }
+
+    expansion
+        .parse()
+        .map_err(|_| Error::new(call_site, "failed to generate the embedded code"))
+}
+
+fn parse_args(input: TokenStream) -> Result<Args, Error> {
+    let mut tokens = input.into_iter();
+
+    let path = match tokens.next() {
+        Some(TokenTree::Literal(literal)) => parse_string(&literal)?,
+        Some(other) => return Err(Error::new(other.span(), "expected a path string")),
+        None => return Err(Error::new(Span::call_site(), "expected a path string")),
+    };
+
+    let mut entry = None;
+    let mut stage = None;
+    let mut target = None;
+    let mut profile = None;
+    let mut defines = Vec::new();
+
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => {}
+            Some(other) => return Err(Error::new(other.span(), "expected `,`")),
+            None => break,
+        }
+
+        let key = match tokens.next() {
+            Some(TokenTree::Ident(ident)) => ident,
+            Some(other) => return Err(Error::new(other.span(), "expected an argument name")),
+            // Trailing comma.
+            None => break,
+        };
+        match tokens.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '=' => {}
+            Some(other) => return Err(Error::new(other.span(), "expected `=`")),
+            None => return Err(Error::new(key.span(), "expected `=` after argument name")),
+        }
+        let value = tokens
+            .next()
+            .ok_or_else(|| Error::new(key.span(), "expected a value"))?;
+
+        match key.to_string().as_str() {
+            "entry" => entry = Some(expect_string(&value)?),
+            "profile" => profile = Some(expect_string(&value)?),
+            "define" => {
+                let define = expect_string(&value)?;
+                let mut parts = define.splitn(2, '=');
+                let name = parts.next().unwrap_or_default().to_string();
+                let value = parts.next().unwrap_or_default().to_string();
+                defines.push((name, value));
+            }
+            "stage" => {
+                let name = expect_ident(&value)?;
+                stage = Some(find(STAGES, &name).ok_or_else(|| {
+                    Error::new(value.span(), format!("unknown stage `{}`", name))
+                })?);
+            }
+            "target" => {
+                let name = expect_ident(&value)?;
+                target = Some(find(TARGETS, &name).ok_or_else(|| {
+                    Error::new(value.span(), format!("unknown target `{}`", name))
+                })?);
+            }
+            other => {
+                return Err(Error::new(
+                    key.span(),
+                    format!("unknown argument `{}`", other),
+                ))
+            }
+        }
+    }
+
+    let missing = |name: &str| Error::new(Span::call_site(), format!("missing `{} = ...`", name));
+    Ok(Args {
+        path,
+        entry: entry.ok_or_else(|| missing("entry"))?,
+        stage: stage.ok_or_else(|| missing("stage"))?,
+        target: target.ok_or_else(|| missing("target"))?,
+        profile,
+        defines,
+    })
+}
+
+fn expect_string(token: &TokenTree) -> Result<String, Error> {
+    match token {
+        TokenTree::Literal(literal) => parse_string(literal),
+        other => Err(Error::new(other.span(), "expected a string")),
+    }
+}
+
+fn expect_ident(token: &TokenTree) -> Result<String, Error> {
+    match token {
+        TokenTree::Ident(ident) => Ok(ident.to_string()),
+        other => Err(Error::new(other.span(), "expected a name, e.g. `Compute`")),
+    }
+}
+
+/// Only plain string literals with the common escapes are supported, which covers paths and defines.
+fn parse_string(literal: &Literal) -> Result<String, Error> {
+    let text = literal.to_string();
+    if !(text.len() >= 2 && text.starts_with('"') && text.ends_with('"')) {
+        return Err(Error::new(literal.span(), "expected a string"));
+    }
+
+    let mut value = String::new();
+    let mut chars = text[1..text.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => value.push('\\'),
+            Some('"') => value.push('"'),
+            Some('\'') => value.push('\''),
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            _ => return Err(Error::new(literal.span(), "unsupported escape in string")),
+        }
+    }
+    Ok(value)
+}
+
+/// The stages an entry point can have, named as in Rust.
+const STAGES: &[(&str, Stage)] = &[
+    ("Vertex", Stage::Vertex),
+    ("Hull", Stage::Hull),
+    ("Domain", Stage::Domain),
+    ("Geometry", Stage::Geometry),
+    ("Fragment", Stage::Fragment),
+    ("Compute", Stage::Compute),
+    ("RayGeneration", Stage::RayGeneration),
+    ("Intersection", Stage::Intersection),
+    ("AnyHit", Stage::AnyHit),
+    ("ClosestHit", Stage::ClosestHit),
+    ("Miss", Stage::Miss),
+    ("Callable", Stage::Callable),
+];
+
+/// The targets that produce shader code, named as in Rust.
+const TARGETS: &[(&str, CompileTarget)] = &[
+    ("Hlsl", CompileTarget::Hlsl),
+    ("Glsl", CompileTarget::Glsl),
+    ("GlslVulkan", CompileTarget::GlslVulkan),
+    ("GlslVulkanOneDesc", CompileTarget::GlslVulkanOneDesc),
+    ("Spirv", CompileTarget::Spirv),
+    ("SpirvAsm", CompileTarget::SpirvAsm),
+    ("Dxbc", CompileTarget::Dxbc),
+    ("DxbcAsm", CompileTarget::DxbcAsm),
+    ("Dxil", CompileTarget::Dxil),
+    ("DxilAsm", CompileTarget::DxilAsm),
+];
+
+fn find<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|&&(n, _)| n == name)
+        .map(|&(_, value)| value)
+}
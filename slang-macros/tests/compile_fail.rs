@@ -0,0 +1,6 @@
+/// Checks that `include_slang!` reports invalid arguments at the argument, before compiling anything.
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,20 @@
+use slang_macros::include_slang;
+
+static NO_PATH: &[u8] = include_slang!(entry = "main", stage = Compute, target = Dxil);
+static NOT_A_STRING: &[u8] = include_slang!(shader, entry = "main", stage = Compute, target = Dxil);
+static NO_COMMA: &[u8] = include_slang!("shader.slang" entry = "main", stage = Compute, target = Dxil);
+static NO_EQUALS: &[u8] = include_slang!("shader.slang", entry "main", stage = Compute, target = Dxil);
+static NO_VALUE: &[u8] = include_slang!("shader.slang", stage = Compute, target = Dxil, entry =);
+static UNKNOWN_ARGUMENT: &[u8] =
+    include_slang!("shader.slang", entry = "main", stage = Compute, target = Dxil, opt = "3");
+static ENTRY_NOT_A_STRING: &[u8] = include_slang!("shader.slang", entry = main, stage = Compute, target = Dxil);
+static STAGE_NOT_A_NAME: &[u8] =
+    include_slang!("shader.slang", entry = "main", stage = "compute", target = Dxil);
+static UNKNOWN_STAGE: &[u8] = include_slang!("shader.slang", entry = "main", stage = Compoot, target = Dxil);
+static UNKNOWN_TARGET: &[u8] = include_slang!("shader.slang", entry = "main", stage = Compute, target = Dxbk);
+static NO_STAGE: &[u8] = include_slang!("shader.slang", entry = "main", stage = None, target = Dxil);
+static NO_TARGET: &[u8] = include_slang!("shader.slang", entry = "main", stage = Compute, target = TargetUnknown);
+static BAD_ESCAPE: &[u8] = include_slang!("shader\x41.slang", entry = "main", stage = Compute, target = Dxil);
+static MISSING_STAGE: &[u8] = include_slang!("shader.slang", entry = "main", target = Dxil,);
+
+fn main() {}
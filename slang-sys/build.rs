@@ -136,8 +136,11 @@ fn generate_bindings(slang_dir: &Path) {
         .generate()
         .expect("Could not generate bindings");
 
-    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("bindings.rs");
     bindings
-        .write_to_file(out_path.join("bindings.rs"))
+        .write_to_file(&out_path)
         .expect("Could not write binding file");
+
+    // Available to the build scripts of dependents as `DEP_SLANG_BINDINGS`, `slang` generates its enums from it.
+    println!("cargo:bindings={}", out_path.display());
 }
@@ -0,0 +1,341 @@
+//! Generates the safe enums in `src/enums.rs` from the constants in `slang-sys`'s bindings, so they always match the
+//! `slang.h` of the Slang release `slang-sys` downloads.
+//!
+//! bindgen gives each anonymous enum in `slang.h` its own type, e.g. `_bindgen_ty_3`, so a family is every constant
+//! with the same type as a known member of it. Constants with the same value as an earlier one become aliases.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+/// An enum generated with `slang_enum!`.
+struct Enum {
+    name: &'static str,
+    /// Any constant in the family.
+    anchor: &'static str,
+    /// Stripped from constants to get variant and `slangc` names, the first that matches is used.
+    prefixes: &'static [&'static str],
+    /// `slangc` names that aren't the kebab-case constant name.
+    names: &'static [(&'static str, &'static str)],
+    /// Extra names `from_str` accepts.
+    aliases: &'static [(&'static str, &'static str)],
+}
+
+/// A `bitflags!` struct.
+struct Flags {
+    name: &'static str,
+    anchor: &'static str,
+    prefix: &'static str,
+}
+
+const ENUMS: &[Enum] = &[
+    Enum {
+        name: "Severity",
+        anchor: "SLANG_SEVERITY_ERROR",
+        prefixes: &["SLANG_SEVERITY_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "BindableResourceType",
+        anchor: "SLANG_TEXTURE",
+        prefixes: &["SLANG_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "CompileTarget",
+        anchor: "SLANG_HLSL",
+        prefixes: &["SLANG_"],
+        names: &[
+            ("SLANG_TARGET_UNKNOWN", "unknown"),
+            ("SLANG_TARGET_NONE", "none"),
+        ],
+        aliases: &[
+            ("SLANG_SPIRV_ASM", "spirv-assembly"),
+            ("SLANG_DXBC_ASM", "dxbc-assembly"),
+            ("SLANG_DXIL_ASM", "dxil-assembly"),
+        ],
+    },
+    Enum {
+        name: "ContainerFormat",
+        anchor: "SLANG_CONTAINER_FORMAT_NONE",
+        prefixes: &["SLANG_"],
+        names: &[
+            ("SLANG_CONTAINER_FORMAT_NONE", "none"),
+            ("SLANG_CONTAINER_FORMAT_SLANG_MODULE", "slang-module"),
+        ],
+        aliases: &[],
+    },
+    Enum {
+        name: "PassThrough",
+        anchor: "SLANG_PASS_THROUGH_DXC",
+        prefixes: &["SLANG_PASS_THROUGH_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "FloatingPointMode",
+        anchor: "SLANG_FLOATING_POINT_MODE_FAST",
+        prefixes: &["SLANG_FLOATING_POINT_MODE_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "LineDirectiveMode",
+        anchor: "SLANG_LINE_DIRECTIVE_MODE_STANDARD",
+        prefixes: &["SLANG_LINE_DIRECTIVE_MODE_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "SourceLanguage",
+        anchor: "SLANG_SOURCE_LANGUAGE_SLANG",
+        prefixes: &["SLANG_SOURCE_LANGUAGE_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "MatrixLayoutMode",
+        anchor: "SLANG_MATRIX_LAYOUT_ROW_MAJOR",
+        prefixes: &["SLANG_MATRIX_LAYOUT_MODE_", "SLANG_MATRIX_LAYOUT_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "Stage",
+        anchor: "SLANG_STAGE_VERTEX",
+        prefixes: &["SLANG_STAGE_"],
+        names: &[
+            ("SLANG_STAGE_RAY_GENERATION", "raygeneration"),
+            ("SLANG_STAGE_ANY_HIT", "anyhit"),
+            ("SLANG_STAGE_CLOSEST_HIT", "closesthit"),
+        ],
+        aliases: &[],
+    },
+    Enum {
+        name: "DebugInfoLevel",
+        anchor: "SLANG_DEBUG_INFO_LEVEL_STANDARD",
+        prefixes: &["SLANG_DEBUG_INFO_LEVEL_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "OptimizationLevel",
+        anchor: "SLANG_OPTIMIZATION_LEVEL_DEFAULT",
+        prefixes: &["SLANG_OPTIMIZATION_LEVEL_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "ParameterCategory",
+        anchor: "SLANG_PARAMETER_CATEGORY_UNIFORM",
+        prefixes: &["SLANG_PARAMETER_CATEGORY_"],
+        names: &[],
+        aliases: &[],
+    },
+    Enum {
+        name: "TypeKind",
+        anchor: "SLANG_TYPE_KIND_STRUCT",
+        prefixes: &["SLANG_TYPE_KIND_"],
+        names: &[],
+        aliases: &[],
+    },
+];
+
+const FLAGS: &[Flags] = &[
+    Flags {
+        name: "CompileFlags",
+        anchor: "SLANG_COMPILE_FLAG_NO_CODEGEN",
+        prefix: "SLANG_COMPILE_FLAG_",
+    },
+    Flags {
+        name: "TargetFlags",
+        anchor: "SLANG_TARGET_FLAG_PARAMETER_BLOCKS_USE_REGISTER_SPACES",
+        prefix: "SLANG_TARGET_FLAG_",
+    },
+];
+
+/// `pub const NAME: TYPE = VALUE;` from the bindings.
+struct Constant {
+    name: String,
+    ty: String,
+    value: i64,
+}
+
+fn main() {
+    // Set by `slang-sys`'s build script through its `links = "slang"` key.
+    let bindings_path = env::var("DEP_SLANG_BINDINGS")
+        .expect("slang-sys didn't report where its bindings are (DEP_SLANG_BINDINGS)");
+    println!("cargo:rerun-if-env-changed=DEP_SLANG_BINDINGS");
+    println!("cargo:rerun-if-changed={}", bindings_path);
+    println!("cargo:rerun-if-changed=build.rs");
+    // Lets `tests/enum_coverage.rs` read the same bindings.
+    println!("cargo:rustc-env=SLANG_BINDINGS={}", bindings_path);
+
+    let bindings = fs::read_to_string(&bindings_path).expect("Could not read bindings");
+    let constants = parse_constants(&bindings);
+
+    let mut out = String::new();
+    writeln!(out, "// Generated by `build.rs` from `{}`.", bindings_path).unwrap();
+    for family in ENUMS {
+        write_enum(&mut out, family, &constants);
+    }
+    for flags in FLAGS {
+        write_flags(&mut out, flags, &constants);
+    }
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("enums.rs");
+    fs::write(out_path, out).expect("Could not write enums");
+}
+
+fn parse_constants(bindings: &str) -> Vec<Constant> {
+    // Doc attributes come before `pub const`, so only the text after it up to the `;` matters. Whitespace is
+    // ignored, bindgen only formats its output if rustfmt is installed.
+    bindings
+        .match_indices("pub const ")
+        .filter_map(|(start, _)| {
+            let rest = &bindings[start + "pub const ".len()..];
+            let declaration = &rest[..rest.find(';')?];
+            let colon = declaration.find(':')?;
+            let equals = declaration.find('=')?;
+            let name = declaration[..colon].trim();
+            let ty = declaration[colon + 1..equals].trim();
+            let value = declaration[equals + 1..].replace(char::is_whitespace, "");
+            Some(Constant {
+                name: name.to_string(),
+                ty: ty.to_string(),
+                value: value.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Every constant with the same type as `anchor`, except the `_COUNT`/`_COUNT_OF` markers.
+fn members<'a>(constants: &'a [Constant], anchor: &str) -> Vec<&'a Constant> {
+    let ty = &constants
+        .iter()
+        .find(|constant| constant.name == anchor)
+        .unwrap_or_else(|| panic!("`{}` isn't in the bindings", anchor))
+        .ty;
+    if !ty.starts_with("_bindgen_ty_") {
+        panic!(
+            "`{}` has the type `{}`, not an anonymous enum's, so its family can't be found",
+            anchor, ty
+        );
+    }
+
+    constants
+        .iter()
+        .filter(|constant| &constant.ty == ty)
+        .filter(|constant| {
+            !constant.name.ends_with("_COUNT") && !constant.name.ends_with("_COUNT_OF")
+        })
+        .collect()
+}
+
+fn write_enum(out: &mut String, family: &Enum, constants: &[Constant]) {
+    let mut variants: Vec<(&Constant, Vec<String>)> = Vec::new();
+    for constant in members(constants, family.anchor) {
+        let name = family
+            .names
+            .iter()
+            .find(|&&(c, _)| c == constant.name)
+            .map_or_else(
+                || kebab_case(strip(&constant.name, family.prefixes)),
+                |&(_, name)| name.to_string(),
+            );
+
+        match variants.iter_mut().find(|(c, _)| c.value == constant.value) {
+            Some((_, names)) => names.push(name),
+            None => variants.push((constant, vec![name])),
+        }
+    }
+    for &(constant, alias) in family.aliases {
+        if let Some((_, names)) = variants.iter_mut().find(|(c, _)| c.name == constant) {
+            names.push(alias.to_string());
+        }
+    }
+
+    writeln!(out, "\nslang_enum! {{").unwrap();
+    write_doc(
+        out,
+        "    ",
+        &format!("Mirrors `Slang{}` in `slang.h`.", family.name),
+    );
+    writeln!(out, "    pub enum {} {{", family.name).unwrap();
+    for (constant, names) in &variants {
+        let quoted = names
+            .iter()
+            .map(|name| format!("`{}`", name))
+            .collect::<Vec<_>>();
+        write_doc(
+            out,
+            "        ",
+            &format!("`{}`, parsed from {}.", constant.name, quoted.join(" or ")),
+        );
+        let names = names
+            .iter()
+            .map(|name| format!("{:?}", name))
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "        {} = slang_sys::{} => {},",
+            camel_case(strip(&constant.name, family.prefixes)),
+            constant.name,
+            names.join(" | ")
+        )
+        .unwrap();
+    }
+    writeln!(out, "    }}\n}}").unwrap();
+}
+
+fn write_flags(out: &mut String, flags: &Flags, constants: &[Constant]) {
+    writeln!(out, "\nbitflags! {{").unwrap();
+    write_doc(
+        out,
+        "    ",
+        &format!("Mirrors `Slang{}` in `slang.h`.", flags.name),
+    );
+    writeln!(out, "    pub struct {}: i32 {{", flags.name).unwrap();
+    for constant in members(constants, flags.anchor) {
+        write_doc(out, "        ", &format!("`{}`.", constant.name));
+        writeln!(
+            out,
+            "        const {} = slang_sys::{};",
+            strip(&constant.name, &[flags.prefix]),
+            constant.name
+        )
+        .unwrap();
+    }
+    writeln!(out, "    }}\n}}").unwrap();
+}
+
+fn write_doc(out: &mut String, indent: &str, doc: &str) {
+    writeln!(out, "{}#[doc = {:?}]", indent, doc).unwrap();
+}
+
+fn strip<'a>(name: &'a str, prefixes: &[&str]) -> &'a str {
+    prefixes
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or_else(|| panic!("`{}` doesn't start with any of {:?}", name, prefixes))
+}
+
+/// `RAY_GENERATION` to `RayGeneration`.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+            })
+        })
+        .collect()
+}
+
+/// `RAY_GENERATION` to `ray-generation`.
+fn kebab_case(name: &str) -> String {
+    name.to_ascii_lowercase().replace('_', "-")
+}
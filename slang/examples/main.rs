@@ -1,5 +0,0 @@
-fn main() {
-    println!("hello");
-    dbg!(slang::Session::new());
-    println!("world");
-}
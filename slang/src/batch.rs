@@ -0,0 +1,196 @@
+//! Compiling many independent requests in parallel.
+//!
+//! Each job is a `CompileRequestBuilder`. Jobs are handed out to a fixed number of worker threads, each of which
+//! compiles with its own `Session` taken from a `SessionPool`.
+//!
+//! ```no_run
+//! use slang::batch::Batch;
+//! use slang::*;
+//!
+//! let jobs = ["0", "1"]
+//!     .iter()
+//!     .map(|value| {
+//!         let mut builder = CompileRequestBuilder::new();
+//!         builder.add_target(TargetDesc::new(CompileTarget::Spirv).profile("glsl_450"));
+//!         let unit = builder.add_translation_unit(
+//!             TranslationUnitDesc::new(SourceLanguage::Slang, "shader")
+//!                 .source_string("shader.slang", "[numthreads(1, 1, 1)] void main() {}"),
+//!         );
+//!         builder.add_entry_point(unit, "main", Stage::Compute);
+//!         builder.define("VARIANT", *value);
+//!         builder
+//!     })
+//!     .collect::<Vec<_>>();
+//!
+//! let results = Batch::new().compile_with_progress(&jobs, |progress| {
+//!     println!("{}/{}", progress.completed, progress.total);
+//! });
+//! ```
+
+use crate::{CompileOutput, CompileRequestBuilder, Result, SessionPool};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Reported after each job finishes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Progress {
+    /// The index of the job that just finished.
+    pub job: usize,
+    /// The number of jobs finished so far, including this one.
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// The outcome of one job.
+#[derive(Debug)]
+pub struct JobResult {
+    pub output: Result<CompileOutput>,
+    /// Everything Slang reported for the job, including errors when `output` is an error. Empty if the request
+    /// couldn't be built.
+    pub diagnostics: String,
+}
+
+#[derive(Debug)]
+pub struct Batch {
+    workers: usize,
+    pool: SessionPool,
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self {
+            workers: thread::available_parallelism().map_or(1, |count| count.get()),
+            pool: SessionPool::new(),
+        }
+    }
+}
+
+impl Batch {
+    /// Uses one worker per available CPU.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The maximum number of worker threads. Values less than one are treated as one.
+    pub fn workers(&mut self, workers: usize) -> &mut Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Builtins registered with every worker's session, see `SessionPool::add_builtins`.
+    pub fn add_builtins(
+        &mut self,
+        source_path: impl Into<String>,
+        source_string: impl Into<String>,
+    ) -> &mut Self {
+        self.pool.add_builtins(source_path, source_string);
+        self
+    }
+
+    /// Compiles every job, returning results in the same order as `jobs`.
+    pub fn compile(&self, jobs: &[CompileRequestBuilder]) -> Vec<JobResult> {
+        self.compile_with_progress(jobs, |_| {})
+    }
+
+    /// Like `compile`, calling `progress` from the worker threads as each job finishes.
+    pub fn compile_with_progress(
+        &self,
+        jobs: &[CompileRequestBuilder],
+        progress: impl Fn(Progress) + Sync,
+    ) -> Vec<JobResult> {
+        let next_job = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let results = Mutex::new((0..jobs.len()).map(|_| None).collect::<Vec<_>>());
+
+        thread::scope(|scope| {
+            for _ in 0..self.workers.min(jobs.len()) {
+                scope.spawn(|| {
+                    let mut session = None;
+
+                    loop {
+                        let index = next_job.fetch_add(1, Ordering::Relaxed);
+                        let job = match jobs.get(index) {
+                            Some(job) => job,
+                            None => break,
+                        };
+
+                        if session.is_none() {
+                            match self.pool.get() {
+                                Ok(pooled) => session = Some(pooled),
+                                Err(error) => {
+                                    // Leave the session empty so the next job tries again.
+                                    let result = JobResult {
+                                        output: Err(error),
+                                        diagnostics: String::new(),
+                                    };
+                                    finish(
+                                        &results,
+                                        index,
+                                        result,
+                                        &completed,
+                                        jobs.len(),
+                                        &progress,
+                                    );
+                                    continue;
+                                }
+                            }
+                        }
+
+                        let result = compile_job(job, session.as_ref().unwrap());
+                        finish(&results, index, result, &completed, jobs.len(), &progress);
+                    }
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .into_iter()
+            .map(|result| result.expect("every job is compiled by some worker"))
+            .collect()
+    }
+}
+
+fn compile_job(job: &CompileRequestBuilder, session: &crate::Session) -> JobResult {
+    let mut request = match job.build(session) {
+        Ok(request) => request,
+        Err(error) => {
+            return JobResult {
+                output: Err(error),
+                diagnostics: String::new(),
+            }
+        }
+    };
+
+    let output = job.compile_request(&mut request);
+
+    JobResult {
+        output,
+        diagnostics: request
+            .get_diagnostic_output()
+            .to_string_lossy()
+            .into_owned(),
+    }
+}
+
+fn finish(
+    results: &Mutex<Vec<Option<JobResult>>>,
+    index: usize,
+    result: JobResult,
+    completed: &AtomicUsize,
+    total: usize,
+    progress: &(impl Fn(Progress) + Sync),
+) {
+    results
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())[index] = Some(result);
+
+    let completed = completed.fetch_add(1, Ordering::Relaxed) + 1;
+    progress(Progress {
+        job: index,
+        completed,
+        total,
+    });
+}
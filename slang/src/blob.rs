@@ -0,0 +1,193 @@
+use slang_sys::*;
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The `ISlangBlob` vtable (`ISlangUnknown` followed by the blob methods), which `bindgen` leaves opaque.
+#[repr(C)]
+struct BlobVtable {
+    query_interface: unsafe extern "system" fn(
+        this: *mut ISlangBlob,
+        uuid: *const c_void,
+        out_object: *mut *mut c_void,
+    ) -> SlangResult,
+    add_ref: unsafe extern "system" fn(this: *mut ISlangBlob) -> u32,
+    release: unsafe extern "system" fn(this: *mut ISlangBlob) -> u32,
+    get_buffer_pointer: unsafe extern "system" fn(this: *mut ISlangBlob) -> *const c_void,
+    get_buffer_size: unsafe extern "system" fn(this: *mut ISlangBlob) -> usize,
+}
+
+/// An owned reference to an `ISlangBlob`.
+pub struct Blob {
+    inner: *mut ISlangBlob,
+}
+
+impl Blob {
+    /// Takes ownership of one reference to `inner`.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must be a valid, non-null `ISlangBlob` whose reference is transferred to the `Blob`.
+    pub unsafe fn from_raw(inner: *mut ISlangBlob) -> Self {
+        Self { inner }
+    }
+
+    /// Retreives the inner value.
+    pub fn get(&self) -> *mut ISlangBlob {
+        self.inner
+    }
+
+    fn vtable(&self) -> &BlobVtable {
+        unsafe { &**(self.inner as *mut *const BlobVtable) }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { (self.vtable().get_buffer_size)(self.inner) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe {
+            let len = self.len();
+            if len == 0 {
+                &[]
+            } else {
+                let ptr = (self.vtable().get_buffer_pointer)(self.inner);
+                std::slice::from_raw_parts(ptr as *const u8, len)
+            }
+        }
+    }
+}
+
+impl AsRef<[u8]> for Blob {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl Clone for Blob {
+    fn clone(&self) -> Self {
+        unsafe {
+            (self.vtable().add_ref)(self.inner);
+            Self::from_raw(self.inner)
+        }
+    }
+}
+
+impl Drop for Blob {
+    fn drop(&mut self) {
+        unsafe {
+            (self.vtable().release)(self.inner);
+        }
+    }
+}
+
+/// Copies `data` into a new blob, e.g. for `CompileRequest::add_translation_unit_source_blob`.
+impl From<&[u8]> for Blob {
+    fn from(data: &[u8]) -> Self {
+        Self::from(data.to_vec())
+    }
+}
+
+/// Moves `data` into a new blob without copying it.
+impl From<Vec<u8>> for Blob {
+    fn from(data: Vec<u8>) -> Self {
+        let blob = Box::new(OwnedBlob {
+            vtable: &OWNED_BLOB_VTABLE,
+            ref_count: AtomicU32::new(1),
+            data: data.into_boxed_slice(),
+        });
+        unsafe { Self::from_raw(Box::into_raw(blob) as *mut ISlangBlob) }
+    }
+}
+
+impl std::fmt::Debug for Blob {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Blob")
+            .field("inner", &self.inner)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Matches `SlangUUID`.
+#[repr(C)]
+#[derive(PartialEq)]
+struct Uuid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+const ISLANG_UNKNOWN_UUID: Uuid = Uuid {
+    data1: 0x0000_0000,
+    data2: 0x0000,
+    data3: 0x0000,
+    data4: [0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46],
+};
+
+const ISLANG_BLOB_UUID: Uuid = Uuid {
+    data1: 0x8ba5_fb08,
+    data2: 0x5195,
+    data3: 0x40e2,
+    data4: [0xac, 0x58, 0x0d, 0x98, 0x9c, 0x3a, 0x01, 0x02],
+};
+
+/// An `ISlangBlob` implemented in Rust, owning its data.
+#[repr(C)]
+struct OwnedBlob {
+    vtable: *const BlobVtable,
+    ref_count: AtomicU32,
+    data: Box<[u8]>,
+}
+
+static OWNED_BLOB_VTABLE: BlobVtable = BlobVtable {
+    query_interface: owned_blob_query_interface,
+    add_ref: owned_blob_add_ref,
+    release: owned_blob_release,
+    get_buffer_pointer: owned_blob_get_buffer_pointer,
+    get_buffer_size: owned_blob_get_buffer_size,
+};
+
+unsafe extern "system" fn owned_blob_query_interface(
+    this: *mut ISlangBlob,
+    uuid: *const c_void,
+    out_object: *mut *mut c_void,
+) -> SlangResult {
+    let uuid = &*(uuid as *const Uuid);
+    if *uuid == ISLANG_UNKNOWN_UUID || *uuid == ISLANG_BLOB_UUID {
+        owned_blob_add_ref(this);
+        *out_object = this as *mut c_void;
+        0
+    } else {
+        *out_object = std::ptr::null_mut();
+        crate::SlangResult::NO_INTERFACE.0
+    }
+}
+
+unsafe extern "system" fn owned_blob_add_ref(this: *mut ISlangBlob) -> u32 {
+    let blob = &*(this as *const OwnedBlob);
+    blob.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn owned_blob_release(this: *mut ISlangBlob) -> u32 {
+    let blob = &*(this as *const OwnedBlob);
+    let remaining = blob.ref_count.fetch_sub(1, Ordering::AcqRel) - 1;
+    if remaining == 0 {
+        drop(Box::from_raw(this as *mut OwnedBlob));
+    }
+    remaining
+}
+
+unsafe extern "system" fn owned_blob_get_buffer_pointer(this: *mut ISlangBlob) -> *const c_void {
+    let blob = &*(this as *const OwnedBlob);
+    blob.data.as_ptr() as *const c_void
+}
+
+unsafe extern "system" fn owned_blob_get_buffer_size(this: *mut ISlangBlob) -> usize {
+    let blob = &*(this as *const OwnedBlob);
+    blob.data.len()
+}
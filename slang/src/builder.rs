@@ -0,0 +1,549 @@
+use crate::string::c_str_to_path;
+use crate::{
+    BuilderError, CompileFlags, CompileRequest, CompileTarget, ContainerFormat, DebugInfoLevel,
+    Error, FloatingPointMode, LineDirectiveMode, MatrixLayoutMode, OptimizationLevel, Profile,
+    Result, Session, SourceLanguage, Stage, TargetFlags,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Identifies a target added with `CompileRequestBuilder::add_target`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TargetId(usize);
+
+/// Identifies a translation unit added with `CompileRequestBuilder::add_translation_unit`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TranslationUnitId(usize);
+
+/// Identifies an entry point added with `CompileRequestBuilder::add_entry_point`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EntryPointId(usize);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TargetDesc {
+    target: CompileTarget,
+    profile: Option<Profile>,
+    flags: Option<TargetFlags>,
+    floating_point_mode: Option<FloatingPointMode>,
+    matrix_layout_mode: Option<MatrixLayoutMode>,
+}
+
+impl TargetDesc {
+    pub fn new(target: CompileTarget) -> Self {
+        Self {
+            target,
+            profile: None,
+            flags: None,
+            floating_point_mode: None,
+            matrix_layout_mode: None,
+        }
+    }
+
+    /// A `Profile` or its name, e.g. `Profile::Sm(5, 0)` or `"glsl_450"`. Building fails if it can't be used with
+    /// this target or Slang doesn't recognize it.
+    pub fn profile(mut self, profile: impl Into<Profile>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    pub fn flags(mut self, flags: TargetFlags) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn floating_point_mode(mut self, mode: FloatingPointMode) -> Self {
+        self.floating_point_mode = Some(mode);
+        self
+    }
+
+    pub fn matrix_layout_mode(mut self, mode: MatrixLayoutMode) -> Self {
+        self.matrix_layout_mode = Some(mode);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// Source code held in memory. `path` is used for diagnostics and resolving relative includes.
+    String { path: String, source: String },
+    /// Like `String`, but for source that isn't UTF-8 or contains NUL bytes.
+    Bytes { path: String, source: Vec<u8> },
+    /// A file Slang reads when compiling.
+    File(PathBuf),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TranslationUnitDesc {
+    language: SourceLanguage,
+    name: String,
+    sources: Vec<Source>,
+    defines: Vec<(String, String)>,
+}
+
+impl TranslationUnitDesc {
+    pub fn new(language: SourceLanguage, name: impl Into<String>) -> Self {
+        Self {
+            language,
+            name: name.into(),
+            sources: Vec::new(),
+            defines: Vec::new(),
+        }
+    }
+
+    pub fn source_string(mut self, path: impl Into<String>, source: impl Into<String>) -> Self {
+        self.sources.push(Source::String {
+            path: path.into(),
+            source: source.into(),
+        });
+        self
+    }
+
+    pub fn source_bytes(mut self, path: impl Into<String>, source: impl Into<Vec<u8>>) -> Self {
+        self.sources.push(Source::Bytes {
+            path: path.into(),
+            source: source.into(),
+        });
+        self
+    }
+
+    pub fn source_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::File(path.into()));
+        self
+    }
+
+    /// A preprocessor define that only applies to this translation unit.
+    pub fn define(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.push((key.into(), value.into()));
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct EntryPointDesc {
+    translation_unit: TranslationUnitId,
+    name: String,
+    stage: Stage,
+}
+
+/// Collects everything needed for a `CompileRequest` as owned values, so it can be validated, stored and
+/// compiled in one call.
+///
+/// ```no_run
+/// use slang::*;
+///
+/// let mut builder = CompileRequestBuilder::new();
+/// let spirv = builder.add_target(TargetDesc::new(CompileTarget::Spirv).profile("glsl_450"));
+/// let unit = builder.add_translation_unit(
+///     TranslationUnitDesc::new(SourceLanguage::Slang, "shader")
+///         .source_string("shader.slang", "[numthreads(1, 1, 1)] void main() {}"),
+/// );
+/// let main = builder.add_entry_point(unit, "main", Stage::Compute);
+///
+/// let output = builder.compile(&Session::new()).unwrap();
+/// let code = output.code(main, spirv).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CompileRequestBuilder {
+    targets: Vec<TargetDesc>,
+    translation_units: Vec<TranslationUnitDesc>,
+    entry_points: Vec<EntryPointDesc>,
+    search_paths: Vec<PathBuf>,
+    defines: Vec<(String, String)>,
+    compile_flags: Option<CompileFlags>,
+    matrix_layout_mode: Option<MatrixLayoutMode>,
+    line_directive_mode: Option<LineDirectiveMode>,
+    output_container_format: Option<ContainerFormat>,
+    debug_info_level: Option<DebugInfoLevel>,
+    optimization_level: Option<OptimizationLevel>,
+    library_references: Vec<Vec<u8>>,
+    intermediates: Option<Intermediates>,
+}
+
+impl CompileRequestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_target(&mut self, desc: TargetDesc) -> TargetId {
+        self.targets.push(desc);
+        TargetId(self.targets.len() - 1)
+    }
+
+    pub fn add_translation_unit(&mut self, desc: TranslationUnitDesc) -> TranslationUnitId {
+        self.translation_units.push(desc);
+        TranslationUnitId(self.translation_units.len() - 1)
+    }
+
+    pub fn add_entry_point(
+        &mut self,
+        translation_unit: TranslationUnitId,
+        name: impl Into<String>,
+        stage: Stage,
+    ) -> EntryPointId {
+        self.entry_points.push(EntryPointDesc {
+            translation_unit,
+            name: name.into(),
+            stage,
+        });
+        EntryPointId(self.entry_points.len() - 1)
+    }
+
+    pub fn search_path(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// A preprocessor define that applies to every translation unit.
+    pub fn define(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.defines.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn compile_flags(&mut self, flags: CompileFlags) -> &mut Self {
+        self.compile_flags = Some(flags);
+        self
+    }
+
+    pub fn matrix_layout_mode(&mut self, mode: MatrixLayoutMode) -> &mut Self {
+        self.matrix_layout_mode = Some(mode);
+        self
+    }
+
+    pub fn line_directive_mode(&mut self, mode: LineDirectiveMode) -> &mut Self {
+        self.line_directive_mode = Some(mode);
+        self
+    }
+
+    pub fn debug_info_level(&mut self, level: DebugInfoLevel) -> &mut Self {
+        self.debug_info_level = Some(level);
+        self
+    }
+
+    pub fn optimization_level(&mut self, level: OptimizationLevel) -> &mut Self {
+        self.optimization_level = Some(level);
+        self
+    }
+
+    /// Has Slang write its intermediate files, e.g. the code it passes to downstream compilers, and returns them
+    /// in `CompileOutput::intermediates`, or `CompileError::intermediates` if the compile fails.
+    pub fn dump_intermediates(&mut self, intermediates: Intermediates) -> &mut Self {
+        self.intermediates = Some(intermediates);
+        self
+    }
+
+    pub fn output_container_format(&mut self, format: ContainerFormat) -> &mut Self {
+        self.output_container_format = Some(format);
+        self
+    }
+
+    /// A Slang module from an earlier compile's `CompileOutput::container`, see
+    /// `CompileRequest::add_library_reference`.
+    pub fn library_reference(&mut self, module: impl Into<Vec<u8>>) -> &mut Self {
+        self.library_references.push(module.into());
+        self
+    }
+
+    /// The files added with `TranslationUnitDesc::source_file`, which Slang reads when compiling.
+    pub(crate) fn source_files(&self) -> impl Iterator<Item = &Path> {
+        self.translation_units
+            .iter()
+            .flat_map(|unit| &unit.sources)
+            .filter_map(|source| match source {
+                Source::File(path) => Some(path.as_path()),
+                Source::String { .. } | Source::Bytes { .. } => None,
+            })
+    }
+
+    /// Checks the builder for mistakes that can be caught without Slang, like a profile for the wrong kind of
+    /// target. Ids aren't tied to the builder that returned them, so an id from a different builder is only caught if
+    /// it's out of range for this one.
+    pub fn validate(&self) -> Result<()> {
+        // A Slang module can be produced without any targets.
+        let is_module =
+            self.output_container_format == Some(ContainerFormat::ContainerFormatSlangModule);
+        if self.targets.is_empty() && !is_module {
+            return Err(Error::Builder(BuilderError::NoTargets));
+        }
+
+        for target in &self.targets {
+            if let Some(profile) = &target.profile {
+                if !profile.is_compatible_with(target.target) {
+                    return Err(Error::Builder(BuilderError::IncompatibleProfile {
+                        profile: profile.clone(),
+                        target: target.target,
+                    }));
+                }
+            }
+        }
+
+        for entry_point in &self.entry_points {
+            if entry_point.translation_unit.0 >= self.translation_units.len() {
+                return Err(Error::Builder(BuilderError::UnknownTranslationUnit {
+                    entry_point: entry_point.name.clone(),
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the builder and creates a `CompileRequest` from it, without compiling.
+    pub fn build<'s>(&self, session: &'s Session) -> Result<CompileRequest<'s>> {
+        self.validate()?;
+
+        let request = session.create_compile_request();
+
+        if let Some(flags) = self.compile_flags {
+            request.set_compile_flags(flags);
+        }
+        if let Some(mode) = self.matrix_layout_mode {
+            request.set_matrix_layout_mode(mode);
+        }
+        if let Some(mode) = self.line_directive_mode {
+            request.set_line_directive_mode(mode);
+        }
+        if let Some(format) = self.output_container_format {
+            request.set_output_container_format(format);
+        }
+        if let Some(level) = self.debug_info_level {
+            request.set_debug_info_level(level);
+        }
+        if let Some(level) = self.optimization_level {
+            request.set_optimization_level(level);
+        }
+        if let Some(intermediates) = &self.intermediates {
+            let dir = intermediates.dir();
+            fs::create_dir_all(&dir).map_err(Error::Io)?;
+            request.set_dump_intermediates(true);
+            request.set_dump_intermediate_prefix(dir.join(intermediates_prefix(&request)))?;
+        }
+
+        for path in &self.search_paths {
+            request.add_search_path(path)?;
+        }
+
+        for (key, value) in &self.defines {
+            request.add_preprocessor_define(key, value)?;
+        }
+
+        for module in &self.library_references {
+            request.add_library_reference(module)?;
+        }
+
+        for target in &self.targets {
+            let index = request.add_code_gen_target(target.target);
+
+            if let Some(profile) = &target.profile {
+                let profile_id = session.find_profile(profile)?.ok_or_else(|| {
+                    Error::Builder(BuilderError::UnknownProfile(profile.to_string()))
+                })?;
+                request.set_target_profile(index, profile_id)?;
+            }
+            if let Some(flags) = target.flags {
+                request.set_target_flags(index, flags)?;
+            }
+            if let Some(mode) = target.floating_point_mode {
+                request.set_target_floating_point_mode(index, mode)?;
+            }
+            if let Some(mode) = target.matrix_layout_mode {
+                request.set_target_matrix_layout_mode(index, mode)?;
+            }
+        }
+
+        let mut translation_unit_indices = Vec::with_capacity(self.translation_units.len());
+        for unit in &self.translation_units {
+            let index = request.add_translation_unit(unit.language, &unit.name)?;
+
+            for (key, value) in &unit.defines {
+                request.translation_unit_add_preprocessor_define(index, key, value)?;
+            }
+
+            for source in &unit.sources {
+                match source {
+                    Source::String { path, source } => {
+                        request.add_translation_unit_source_string(index, path, source)?;
+                    }
+                    Source::Bytes { path, source } => {
+                        request.add_translation_unit_source_string_span(index, path, source)?;
+                    }
+                    Source::File(path) => {
+                        request.add_translation_unit_source_file(index, path)?;
+                    }
+                }
+            }
+
+            translation_unit_indices.push(index);
+        }
+
+        for entry_point in &self.entry_points {
+            request.add_entry_point(
+                translation_unit_indices[entry_point.translation_unit.0],
+                &entry_point.name,
+                entry_point.stage,
+            )?;
+        }
+
+        Ok(request)
+    }
+
+    /// Builds and compiles the request, collecting the code for every entry point and target.
+    pub fn compile(&self, session: &Session) -> Result<CompileOutput> {
+        let mut request = self.build(session)?;
+        self.compile_request(&mut request)
+    }
+
+    /// Compiles a request created by `build` and collects its output. If the compile fails with `Error::Compile`,
+    /// the intermediates written before the failure are moved into the error.
+    pub(crate) fn compile_request(&self, request: &mut CompileRequest) -> Result<CompileOutput> {
+        match request.compile() {
+            Ok(()) => self.collect_output(request),
+            Err(Error::Compile(mut error)) => {
+                error.intermediates = self.take_intermediates(request)?;
+                Err(Error::Compile(error))
+            }
+            Err(error) => {
+                // Specialization fails before any code is generated, but clean up in case.
+                let _ = self.take_intermediates(request);
+                Err(error)
+            }
+        }
+    }
+
+    /// Copies the code, diagnostics and dependencies out of `request`, which must have been created by `build` on
+    /// this builder and compiled. Reads the intermediates with `take_intermediates`, so they're deleted from disk
+    /// when kept in memory.
+    pub(crate) fn collect_output(&self, request: &CompileRequest) -> Result<CompileOutput> {
+        let targets = request.code_gen_targets();
+        let mut entry_points = Vec::with_capacity(self.entry_points.len());
+        for (entry_point, index) in self.entry_points.iter().zip(request.entry_points()) {
+            let mut code = Vec::with_capacity(targets.len());
+            for &target in &targets {
+                let blob = request.get_entry_point_code_blob(index, target)?;
+                code.push(blob.as_slice().to_vec());
+            }
+
+            entry_points.push(EntryPointOutput {
+                name: entry_point.name.clone(),
+                stage: entry_point.stage,
+                code,
+            });
+        }
+
+        Ok(CompileOutput {
+            diagnostics: request
+                .get_diagnostic_output()
+                .to_string_lossy()
+                .into_owned(),
+            container: request.get_compile_request_code().to_vec(),
+            dependencies: (0..request.get_dependency_file_count())
+                .map(|index| c_str_to_path(request.get_dependency_file_path(index)))
+                .collect(),
+            entry_points,
+            intermediates: self.take_intermediates(request)?,
+        })
+    }
+
+    /// Reads the intermediate files `request` dumped, deleting them if they're kept in memory. Each is named
+    /// without the per-request prefix, e.g. `0.hlsl`.
+    fn take_intermediates(&self, request: &CompileRequest) -> Result<Vec<(String, Vec<u8>)>> {
+        let intermediates = match &self.intermediates {
+            Some(intermediates) => intermediates,
+            None => return Ok(Vec::new()),
+        };
+
+        let prefix = intermediates_prefix(request);
+        let mut files = Vec::new();
+        for entry in fs::read_dir(intermediates.dir()).map_err(Error::Io)? {
+            let path = entry.map_err(Error::Io)?.path();
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) if name.starts_with(&prefix) => name[prefix.len()..].to_string(),
+                _ => continue,
+            };
+            files.push((name, fs::read(&path).map_err(Error::Io)?));
+            if *intermediates == Intermediates::Memory {
+                fs::remove_file(&path).map_err(Error::Io)?;
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    /// Writes intermediates read back from elsewhere, e.g. a cache, to the directory given to
+    /// `dump_intermediates`, each named `prefix` followed by its name. Does nothing if they're kept in memory.
+    pub(crate) fn restore_intermediates(
+        &self,
+        prefix: &str,
+        intermediates: &[(String, Vec<u8>)],
+    ) -> std::io::Result<()> {
+        let dir = match &self.intermediates {
+            Some(Intermediates::Directory(dir)) => dir,
+            Some(Intermediates::Memory) | None => return Ok(()),
+        };
+
+        fs::create_dir_all(dir)?;
+        for (name, contents) in intermediates {
+            fs::write(dir.join(format!("{}{}", prefix, name)), contents)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where `CompileRequestBuilder::dump_intermediates` writes Slang's intermediate files.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Intermediates {
+    /// Written to this directory, which is created if needed, and left there.
+    Directory(PathBuf),
+    /// Written to the system's temporary directory and deleted once they've been read into the output.
+    Memory,
+}
+
+impl Intermediates {
+    fn dir(&self) -> PathBuf {
+        match self {
+            Intermediates::Directory(dir) => dir.clone(),
+            Intermediates::Memory => std::env::temp_dir().join("slang-rs-intermediates"),
+        }
+    }
+}
+
+/// Unique to the request while it's alive, so concurrent compiles can share a directory.
+fn intermediates_prefix(request: &CompileRequest) -> String {
+    format!("{}-{:x}-", std::process::id(), request.get() as usize)
+}
+
+/// The results of `CompileRequestBuilder::compile`, owning all code and diagnostics.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CompileOutput {
+    /// Warnings and other diagnostics from a successful compile. May be empty.
+    pub diagnostics: String,
+    /// The output container, e.g. a Slang module when `CompileRequestBuilder::output_container_format` was set to
+    /// `ContainerFormat::ContainerFormatSlangModule`. Empty if no container format was set.
+    pub container: Vec<u8>,
+    /// Every file the compile read, including the translation units' own source files and anything they
+    /// `#include` or `import`.
+    pub dependencies: Vec<PathBuf>,
+    /// Indexed by `EntryPointId`.
+    pub entry_points: Vec<EntryPointOutput>,
+    /// The name and contents of each intermediate file, when `CompileRequestBuilder::dump_intermediates` was set.
+    pub intermediates: Vec<(String, Vec<u8>)>,
+}
+
+impl CompileOutput {
+    /// `None` if either id is out of range, e.g. because it's from a different builder.
+    pub fn code(&self, entry_point: EntryPointId, target: TargetId) -> Option<&[u8]> {
+        self.entry_points
+            .get(entry_point.0)?
+            .code
+            .get(target.0)
+            .map(Vec::as_slice)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EntryPointOutput {
+    pub name: String,
+    pub stage: Stage,
+    /// Indexed by `TargetId`.
+    pub code: Vec<Vec<u8>>,
+}
@@ -0,0 +1,390 @@
+//! An on-disk cache of compile outputs, keyed by a hash of everything that goes into the compile.
+//!
+//! The key covers the whole `CompileRequestBuilder` (sources, entry points, targets, profiles, defines, search
+//! paths and flags) and Slang's build tag. Each entry also records the dependency files Slang reported along with
+//! a hash of their contents, so editing an included file invalidates every entry that used it.
+//!
+//! Only successful compiles are cached. Keys come from Rust's `Hash` implementations, which aren't guaranteed to
+//! be stable across compiler versions; a changed key just means a cache miss.
+//!
+//! ```no_run
+//! use slang::cache::CompileCache;
+//! use slang::*;
+//!
+//! let mut builder = CompileRequestBuilder::new();
+//! builder.add_target(TargetDesc::new(CompileTarget::Spirv).profile("glsl_450"));
+//! let source = "#include \"common.slang\"\n[numthreads(1, 1, 1)] void main() {}";
+//! let unit = builder.add_translation_unit(
+//!     TranslationUnitDesc::new(SourceLanguage::Slang, "shader").source_string("shader.slang", source),
+//! );
+//! builder.add_entry_point(unit, "main", Stage::Compute);
+//!
+//! let cache = CompileCache::new("target/shader-cache");
+//! let output = cache.compile(&builder, &Session::new()).unwrap();
+//! ```
+
+use crate::{
+    CompileOutput, CompileRequestBuilder, EntryPointOutput, Error, Result, Session, Stage,
+};
+use std::convert::TryFrom;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Identifies the file format, bumped whenever it changes.
+const MAGIC: &[u8] = b"slang-rs cache 3\n";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CompileCache {
+    dir: PathBuf,
+}
+
+impl CompileCache {
+    /// Stores entries as files in `dir`, which is created when the first entry is written.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The hex encoded hash of `builder` and the Slang version, used as the entry's file name.
+    pub fn key(&self, builder: &CompileRequestBuilder) -> String {
+        let mut hasher = Fnv128::new();
+        crate::get_build_tag_string().to_bytes().hash(&mut hasher);
+        builder.hash(&mut hasher);
+        format!("{:032x}", hasher.finish128())
+    }
+
+    /// Returns the cached output for `builder`, or `None` if there is no entry or a dependency file changed since
+    /// it was written. Unreadable entries are treated as missing.
+    ///
+    /// If `builder` dumps intermediates to a directory, they're written there again, named with the entry's key
+    /// instead of the per-request prefix, e.g. `<key>-0.hlsl`. Failing to write them is treated as a miss.
+    pub fn get(&self, builder: &CompileRequestBuilder) -> Option<CompileOutput> {
+        let key = self.key(builder);
+        let bytes = fs::read(self.dir.join(&key)).ok()?;
+        let entry = Entry::decode(&bytes).ok()?;
+
+        let up_to_date = entry
+            .output
+            .dependencies
+            .iter()
+            .zip(&entry.dependency_hashes)
+            .all(|(path, hash)| hash_file(path) == *hash);
+        if !up_to_date {
+            return None;
+        }
+
+        builder
+            .restore_intermediates(&format!("{}-", key), &entry.output.intermediates)
+            .ok()?;
+        Some(entry.output)
+    }
+
+    /// Returns the cached output for `builder` if it's up to date, otherwise compiles it with `session` and
+    /// stores the result.
+    pub fn compile(
+        &self,
+        builder: &CompileRequestBuilder,
+        session: &Session,
+    ) -> Result<CompileOutput> {
+        if let Some(output) = self.get(builder) {
+            return Ok(output);
+        }
+
+        let output = builder.compile(session)?;
+        let entry = Entry {
+            dependency_hashes: output
+                .dependencies
+                .iter()
+                .map(|path| hash_file(path))
+                .collect(),
+            output,
+        };
+        self.write_entry(builder, &entry).map_err(Error::Io)?;
+
+        Ok(entry.output)
+    }
+
+    /// Deletes every entry.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    fn entry_path(&self, builder: &CompileRequestBuilder) -> PathBuf {
+        self.dir.join(self.key(builder))
+    }
+
+    fn write_entry(&self, builder: &CompileRequestBuilder, entry: &Entry) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        // Write to a temporary file first so concurrent readers never see a partial entry. The counter keeps
+        // threads in the same process that write the same entry from sharing a temporary file.
+        let path = self.entry_path(builder);
+        let temp_path = path.with_extension(format!(
+            "tmp{}-{}",
+            std::process::id(),
+            NEXT_TEMP_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temp_path, entry.encode())?;
+        match fs::rename(&temp_path, &path) {
+            Ok(()) => Ok(()),
+            // On Windows, replacing an entry fails while another process has it open. Whoever wrote it compiled
+            // the same request, so keep theirs.
+            Err(_) if path.is_file() => fs::remove_file(&temp_path),
+            Err(error) => {
+                let _ = fs::remove_file(&temp_path);
+                Err(error)
+            }
+        }
+    }
+}
+
+static NEXT_TEMP_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// `None` for files that can't be read, e.g. the in-memory paths given to `TranslationUnitDesc::source_string`.
+fn hash_file(path: &Path) -> Option<u128> {
+    let contents = fs::read(path).ok()?;
+    let mut hasher = Fnv128::new();
+    hasher.write(&contents);
+    Some(hasher.finish128())
+}
+
+/// 128-bit FNV-1a, used instead of `DefaultHasher` because its output is the same in every process.
+struct Fnv128(u128);
+
+impl Fnv128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        Fnv128(Self::OFFSET_BASIS)
+    }
+
+    fn finish128(&self) -> u128 {
+        self.0
+    }
+}
+
+impl Hasher for Fnv128 {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u128::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0 as u64
+    }
+}
+
+struct Entry {
+    output: CompileOutput,
+    /// Parallel to `output.dependencies`.
+    dependency_hashes: Vec<Option<u128>>,
+}
+
+impl Entry {
+    fn encode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+
+        write_len(&mut out, self.output.dependencies.len());
+        for (path, hash) in self.output.dependencies.iter().zip(&self.dependency_hashes) {
+            write_bytes(&mut out, path.to_string_lossy().as_bytes());
+            match hash {
+                Some(hash) => {
+                    out.push(1);
+                    out.extend_from_slice(&hash.to_le_bytes());
+                }
+                None => out.push(0),
+            }
+        }
+
+        write_bytes(&mut out, self.output.diagnostics.as_bytes());
+        write_bytes(&mut out, &self.output.container);
+        write_len(&mut out, self.output.entry_points.len());
+        for entry_point in &self.output.entry_points {
+            write_bytes(&mut out, entry_point.name.as_bytes());
+            out.extend_from_slice(&(entry_point.stage as i32).to_le_bytes());
+            write_len(&mut out, entry_point.code.len());
+            for code in &entry_point.code {
+                write_bytes(&mut out, code);
+            }
+        }
+        write_len(&mut out, self.output.intermediates.len());
+        for (name, contents) in &self.output.intermediates {
+            write_bytes(&mut out, name.as_bytes());
+            write_bytes(&mut out, contents);
+        }
+
+        out
+    }
+
+    fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        let reader = &mut bytes;
+
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid_data("unknown cache entry format"));
+        }
+
+        let dependency_count = read_len(reader)?;
+        let mut dependencies = Vec::with_capacity(dependency_count.min(1024));
+        let mut dependency_hashes = Vec::with_capacity(dependency_count.min(1024));
+        for _ in 0..dependency_count {
+            let path = PathBuf::from(read_string(reader)?);
+            let hash = match read_array::<1>(reader)?[0] {
+                0 => None,
+                _ => Some(u128::from_le_bytes(read_array(reader)?)),
+            };
+            dependencies.push(path);
+            dependency_hashes.push(hash);
+        }
+
+        let diagnostics = read_string(reader)?;
+        let container = read_bytes(reader)?;
+        let entry_point_count = read_len(reader)?;
+        let mut entry_points = Vec::with_capacity(entry_point_count.min(1024));
+        for _ in 0..entry_point_count {
+            let name = read_string(reader)?;
+            let stage = Stage::try_from(i32::from_le_bytes(read_array(reader)?))
+                .map_err(|_| invalid_data("unknown stage"))?;
+            let code_count = read_len(reader)?;
+            let mut code = Vec::with_capacity(code_count.min(1024));
+            for _ in 0..code_count {
+                code.push(read_bytes(reader)?);
+            }
+            entry_points.push(EntryPointOutput { name, stage, code });
+        }
+        let intermediate_count = read_len(reader)?;
+        let mut intermediates = Vec::with_capacity(intermediate_count.min(1024));
+        for _ in 0..intermediate_count {
+            intermediates.push((read_string(reader)?, read_bytes(reader)?));
+        }
+
+        Ok(Entry {
+            output: CompileOutput {
+                diagnostics,
+                container,
+                dependencies,
+                entry_points,
+                intermediates,
+            },
+            dependency_hashes,
+        })
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_len(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+fn read_array<const N: usize>(reader: &mut &[u8]) -> io::Result<[u8; N]> {
+    let mut array = [0; N];
+    reader.read_exact(&mut array)?;
+    Ok(array)
+}
+
+fn read_len(reader: &mut &[u8]) -> io::Result<usize> {
+    Ok(u64::from_le_bytes(read_array(reader)?) as usize)
+}
+
+fn read_bytes(reader: &mut &[u8]) -> io::Result<Vec<u8>> {
+    let len = read_len(reader)?;
+    if len > reader.len() {
+        return Err(invalid_data("truncated cache entry"));
+    }
+    let (bytes, rest) = reader.split_at(len);
+    *reader = rest;
+    Ok(bytes.to_vec())
+}
+
+fn read_string(reader: &mut &[u8]) -> io::Result<String> {
+    String::from_utf8(read_bytes(reader)?).map_err(|_| invalid_data("invalid UTF-8 in cache entry"))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> Entry {
+        Entry {
+            output: CompileOutput {
+                diagnostics: "shader.slang(1): warning 1: unused".to_string(),
+                container: vec![1, 2, 3],
+                dependencies: vec![PathBuf::from("shader.slang"), PathBuf::from("common.slang")],
+                entry_points: vec![
+                    EntryPointOutput {
+                        name: "vertexMain".to_string(),
+                        stage: Stage::Vertex,
+                        code: vec![vec![0xde, 0xad], vec![]],
+                    },
+                    EntryPointOutput {
+                        name: "fragmentMain".to_string(),
+                        stage: Stage::Fragment,
+                        code: vec![vec![0xbe, 0xef], vec![7]],
+                    },
+                ],
+                intermediates: vec![("shader.spv".to_string(), vec![4, 5])],
+            },
+            dependency_hashes: vec![Some(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef), None],
+        }
+    }
+
+    #[test]
+    fn entry_round_trips() {
+        let entry = entry();
+        let decoded = Entry::decode(&entry.encode()).unwrap();
+        assert_eq!(decoded.output, entry.output);
+        assert_eq!(decoded.dependency_hashes, entry.dependency_hashes);
+
+        let empty = Entry {
+            output: CompileOutput::default(),
+            dependency_hashes: Vec::new(),
+        };
+        let decoded = Entry::decode(&empty.encode()).unwrap();
+        assert_eq!(decoded.output, empty.output);
+        assert!(decoded.dependency_hashes.is_empty());
+    }
+
+    #[test]
+    fn truncated_entries_are_rejected() {
+        let bytes = entry().encode();
+        for len in 0..bytes.len() {
+            assert!(
+                Entry::decode(&bytes[..len]).is_err(),
+                "decoded an entry truncated to {} of {} bytes",
+                len,
+                bytes.len()
+            );
+        }
+    }
+
+    #[test]
+    fn other_formats_are_rejected() {
+        let mut bytes = entry().encode();
+        bytes[MAGIC.len() - 2] ^= 1;
+        assert_eq!(
+            Entry::decode(&bytes).err().map(|error| error.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+}
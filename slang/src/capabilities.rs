@@ -0,0 +1,254 @@
+//! Which targets and downstream compilers the loaded Slang library can use.
+//!
+//! ```no_run
+//! use slang::*;
+//!
+//! let capabilities = Session::new().capabilities();
+//! if !capabilities.supports(CompileTarget::Dxil) {
+//!     eprintln!("{}", capabilities);
+//! }
+//! ```
+
+use crate::{CompileTarget, Error, PassThrough, Session, SlangResult};
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Support {
+    Supported,
+    /// The target needs a downstream compiler, e.g. dxc for DXIL, that Slang couldn't load.
+    MissingPassThrough(PassThrough),
+    /// Slang reported that it isn't supported, for another reason than a missing downstream compiler.
+    Unsupported(SlangResult),
+}
+
+impl Support {
+    pub fn is_supported(self) -> bool {
+        self == Support::Supported
+    }
+}
+
+/// The result of `Session::capabilities`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    pub targets: Vec<(CompileTarget, Support)>,
+    /// `Err` with what Slang reported for each downstream compiler it couldn't load.
+    pub pass_throughs: Vec<(PassThrough, std::result::Result<(), SlangResult>)>,
+}
+
+impl Capabilities {
+    pub(crate) fn probe(session: &Session) -> Self {
+        let pass_throughs = PassThrough::ALL
+            .iter()
+            .filter(|&&pass_through| pass_through != PassThrough::None)
+            .map(|&pass_through| {
+                let support = session
+                    .check_pass_through_support(pass_through)
+                    .map_err(slang_result);
+                (pass_through, support)
+            })
+            .collect::<Vec<_>>();
+
+        let targets = CompileTarget::ALL
+            .iter()
+            .filter(|&&target| {
+                target != CompileTarget::TargetUnknown && target != CompileTarget::TargetNone
+            })
+            .map(|&target| {
+                let result = session
+                    .check_compile_target_support(target)
+                    .map_err(slang_result);
+                (target, target_support(target, result, &pass_throughs))
+            })
+            .collect();
+
+        Self {
+            targets,
+            pass_throughs,
+        }
+    }
+
+    pub fn supports(&self, target: CompileTarget) -> bool {
+        self.target_support(target) == Some(Support::Supported)
+    }
+
+    pub fn supports_pass_through(&self, pass_through: PassThrough) -> bool {
+        self.pass_throughs
+            .iter()
+            .any(|&(p, support)| p == pass_through && support.is_ok())
+    }
+
+    /// `None` for `TargetUnknown` and `TargetNone`, which aren't probed.
+    pub fn target_support(&self, target: CompileTarget) -> Option<Support> {
+        self.targets
+            .iter()
+            .find(|&&(t, _)| t == target)
+            .map(|&(_, support)| support)
+    }
+
+    pub fn supported_targets(&self) -> impl Iterator<Item = CompileTarget> + '_ {
+        self.targets
+            .iter()
+            .filter(|(_, support)| support.is_supported())
+            .map(|&(target, _)| target)
+    }
+}
+
+/// One line per target and downstream compiler, e.g. `Dxil: missing Dxc` or `Dxc: unavailable (E_FAIL)`.
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "targets:")?;
+        for (target, support) in &self.targets {
+            writeln!(f, "  {:?}: {}", target, support)?;
+        }
+        writeln!(f, "downstream compilers:")?;
+        for (pass_through, support) in &self.pass_throughs {
+            match support {
+                Ok(()) => writeln!(f, "  {:?}: available", pass_through)?,
+                Err(result) => writeln!(f, "  {:?}: unavailable ({})", pass_through, result)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Support {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Support::Supported => write!(f, "supported"),
+            Support::MissingPassThrough(pass_through) => write!(f, "missing {:?}", pass_through),
+            Support::Unsupported(result) => write!(f, "unsupported ({})", result),
+        }
+    }
+}
+
+/// Blames a missing downstream compiler for `target` failing, if it needs one that couldn't be loaded.
+fn target_support(
+    target: CompileTarget,
+    result: std::result::Result<(), SlangResult>,
+    pass_throughs: &[(PassThrough, std::result::Result<(), SlangResult>)],
+) -> Support {
+    let error = match result {
+        Ok(()) => return Support::Supported,
+        Err(error) => error,
+    };
+    match required_pass_through(target) {
+        Some(pass_through)
+            if pass_throughs
+                .iter()
+                .any(|&(p, support)| p == pass_through && support.is_err()) =>
+        {
+            Support::MissingPassThrough(pass_through)
+        }
+        _ => Support::Unsupported(error),
+    }
+}
+
+/// The downstream compiler Slang uses to produce `target`, if any.
+fn required_pass_through(target: CompileTarget) -> Option<PassThrough> {
+    match target {
+        CompileTarget::Dxbc | CompileTarget::DxbcAsm => Some(PassThrough::Fxc),
+        CompileTarget::Dxil | CompileTarget::DxilAsm => Some(PassThrough::Dxc),
+        CompileTarget::Spirv | CompileTarget::SpirvAsm => Some(PassThrough::Glslang),
+        _ => None,
+    }
+}
+
+fn slang_result(error: Error) -> SlangResult {
+    match error {
+        Error::Slang(result) => result,
+        _ => SlangResult::FAIL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pass_throughs_for_targets() {
+        assert_eq!(
+            required_pass_through(CompileTarget::Dxbc),
+            Some(PassThrough::Fxc)
+        );
+        assert_eq!(
+            required_pass_through(CompileTarget::DxilAsm),
+            Some(PassThrough::Dxc)
+        );
+        assert_eq!(
+            required_pass_through(CompileTarget::Spirv),
+            Some(PassThrough::Glslang)
+        );
+        assert_eq!(required_pass_through(CompileTarget::Hlsl), None);
+        assert_eq!(required_pass_through(CompileTarget::Glsl), None);
+    }
+
+    #[test]
+    fn missing_pass_throughs_are_blamed() {
+        let pass_throughs = [
+            (PassThrough::Dxc, Err(SlangResult::NOT_FOUND)),
+            (PassThrough::Glslang, Ok(())),
+        ];
+
+        assert_eq!(
+            target_support(CompileTarget::Dxil, Err(SlangResult::FAIL), &pass_throughs),
+            Support::MissingPassThrough(PassThrough::Dxc)
+        );
+        // glslang loaded, so something else is wrong.
+        assert_eq!(
+            target_support(CompileTarget::Spirv, Err(SlangResult::FAIL), &pass_throughs),
+            Support::Unsupported(SlangResult::FAIL)
+        );
+        assert_eq!(
+            target_support(
+                CompileTarget::Glsl,
+                Err(SlangResult::NOT_IMPLEMENTED),
+                &pass_throughs
+            ),
+            Support::Unsupported(SlangResult::NOT_IMPLEMENTED)
+        );
+        assert_eq!(
+            target_support(CompileTarget::Dxil, Ok(()), &pass_throughs),
+            Support::Supported
+        );
+    }
+
+    #[test]
+    fn display() {
+        let capabilities = Capabilities {
+            targets: vec![
+                (CompileTarget::Hlsl, Support::Supported),
+                (
+                    CompileTarget::Dxil,
+                    Support::MissingPassThrough(PassThrough::Dxc),
+                ),
+                (
+                    CompileTarget::Spirv,
+                    Support::Unsupported(SlangResult::FAIL),
+                ),
+            ],
+            pass_throughs: vec![
+                (PassThrough::Dxc, Err(SlangResult::NOT_FOUND)),
+                (PassThrough::Glslang, Ok(())),
+            ],
+        };
+
+        assert_eq!(
+            capabilities.to_string(),
+            "targets:\n\
+             \x20 Hlsl: supported\n\
+             \x20 Dxil: missing Dxc\n\
+             \x20 Spirv: unsupported (E_FAIL)\n\
+             downstream compilers:\n\
+             \x20 Dxc: unavailable (SLANG_E_NOT_FOUND)\n\
+             \x20 Glslang: available\n"
+        );
+        assert!(capabilities.supports(CompileTarget::Hlsl));
+        assert!(!capabilities.supports(CompileTarget::Dxil));
+        assert!(capabilities.supports_pass_through(PassThrough::Glslang));
+        assert!(!capabilities.supports_pass_through(PassThrough::Dxc));
+        assert_eq!(
+            capabilities.supported_targets().collect::<Vec<_>>(),
+            [CompileTarget::Hlsl]
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! D3D12 root signature descriptions derived from reflection.
+//!
+//! Intended for requests targeting `CompileTarget::Dxbc` or `CompileTarget::Dxil`.
+
+use crate::{ParameterCategory, ShaderReflection, Stage, VariableLayoutReflection};
+use bitflags::bitflags;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Matches `D3D12_DESCRIPTOR_RANGE::NumDescriptors == UINT_MAX`.
+pub const DESCRIPTOR_RANGE_UNBOUNDED: u32 = !0;
+
+bitflags! {
+    pub struct RootSignatureFlags: u32 {
+        const ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT = 0x1;
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DescriptorRangeType {
+    Cbv,
+    Srv,
+    Uav,
+    Sampler,
+}
+
+impl DescriptorRangeType {
+    fn from_category(category: ParameterCategory) -> Option<Self> {
+        match category {
+            ParameterCategory::ConstantBuffer => Some(DescriptorRangeType::Cbv),
+            ParameterCategory::ShaderResource => Some(DescriptorRangeType::Srv),
+            ParameterCategory::UnorderedAccess => Some(DescriptorRangeType::Uav),
+            ParameterCategory::SamplerState => Some(DescriptorRangeType::Sampler),
+            _ => None,
+        }
+    }
+
+    /// The HLSL register prefix, e.g. `b` for `b0`.
+    pub fn register_prefix(self) -> char {
+        match self {
+            DescriptorRangeType::Cbv => 'b',
+            DescriptorRangeType::Srv => 't',
+            DescriptorRangeType::Uav => 'u',
+            DescriptorRangeType::Sampler => 's',
+        }
+    }
+
+    fn hlsl_name(self) -> &'static str {
+        match self {
+            DescriptorRangeType::Cbv => "CBV",
+            DescriptorRangeType::Srv => "SRV",
+            DescriptorRangeType::Uav => "UAV",
+            DescriptorRangeType::Sampler => "Sampler",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DescriptorRange {
+    pub range_type: DescriptorRangeType,
+    /// May be `DESCRIPTOR_RANGE_UNBOUNDED`.
+    pub num_descriptors: u32,
+    pub base_shader_register: u32,
+    pub register_space: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RootParameter {
+    DescriptorTable {
+        ranges: Vec<DescriptorRange>,
+    },
+    Constants {
+        shader_register: u32,
+        register_space: u32,
+        num_32bit_values: u32,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RootSignatureDesc {
+    pub parameters: Vec<RootParameter>,
+    pub flags: RootSignatureFlags,
+}
+
+impl Default for RootSignatureFlags {
+    fn default() -> Self {
+        RootSignatureFlags::empty()
+    }
+}
+
+impl RootSignatureDesc {
+    /// Derives a root signature from `reflection`.
+    ///
+    /// Root constants come first, followed by one descriptor table of CBV/SRV/UAV ranges per register space and one
+    /// table of sampler ranges per register space (D3D12 doesn't allow samplers to share a table with other
+    /// descriptors). Constant buffers in the push constant category become root constants.
+    pub fn from_reflection(reflection: ShaderReflection) -> Self {
+        Self::from_reflection_with_root_constants(reflection, &[])
+    }
+
+    /// Like `from_reflection`, but also turns the constant buffers named in `root_constants` into root constants.
+    pub fn from_reflection_with_root_constants(
+        reflection: ShaderReflection,
+        root_constants: &[&str],
+    ) -> Self {
+        let mut collector = Collector {
+            root_constant_names: root_constants,
+            ..Collector::default()
+        };
+
+        for parameter in reflection.parameters() {
+            collector.add_parameter(parameter, 0);
+        }
+
+        let mut flags = RootSignatureFlags::empty();
+        for entry_point in reflection.entry_points() {
+            if entry_point.stage() == Some(Stage::Vertex) {
+                flags |= RootSignatureFlags::ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT;
+            }
+
+            // Varying parameters don't consume any of the categories we care about, so they're skipped naturally.
+            for parameter in entry_point.parameters() {
+                collector.add_parameter(parameter, 0);
+            }
+        }
+
+        collector.finish(flags)
+    }
+
+    /// Renders the root signature in the HLSL root signature string syntax, e.g. for use in a `[RootSignature(...)]`
+    /// attribute or with `/rootsig-define`.
+    pub fn to_hlsl(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for RootSignatureDesc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        let mut separator = |f: &mut fmt::Formatter| -> fmt::Result {
+            if first {
+                first = false;
+                Ok(())
+            } else {
+                write!(f, ", ")
+            }
+        };
+
+        if self
+            .flags
+            .contains(RootSignatureFlags::ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT)
+        {
+            separator(f)?;
+            write!(f, "RootFlags(ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT)")?;
+        }
+
+        for parameter in &self.parameters {
+            separator(f)?;
+            write!(f, "{}", parameter)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for RootParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RootParameter::DescriptorTable { ranges } => {
+                write!(f, "DescriptorTable(")?;
+                for (i, range) in ranges.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", range)?;
+                }
+                write!(f, ")")
+            }
+            RootParameter::Constants {
+                shader_register,
+                register_space,
+                num_32bit_values,
+            } => write!(
+                f,
+                "RootConstants(num32BitConstants={}, b{}, space={})",
+                num_32bit_values, shader_register, register_space
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DescriptorRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}({}{}, numDescriptors=",
+            self.range_type.hlsl_name(),
+            self.range_type.register_prefix(),
+            self.base_shader_register
+        )?;
+        if self.num_descriptors == DESCRIPTOR_RANGE_UNBOUNDED {
+            write!(f, "unbounded")?;
+        } else {
+            write!(f, "{}", self.num_descriptors)?;
+        }
+        write!(f, ", space={})", self.register_space)
+    }
+}
+
+#[derive(Default)]
+struct Collector<'n> {
+    root_constant_names: &'n [&'n str],
+    root_constants: Vec<RootParameter>,
+    // Keyed by register space.
+    resource_ranges: BTreeMap<u32, Vec<DescriptorRange>>,
+    sampler_ranges: BTreeMap<u32, Vec<DescriptorRange>>,
+}
+
+impl Collector<'_> {
+    fn add_parameter(&mut self, parameter: VariableLayoutReflection, space_offset: usize) {
+        let type_layout = parameter.type_layout();
+        let categories = type_layout.categories().collect::<Vec<_>>();
+
+        let is_named_root_constant = parameter
+            .name()
+            .and_then(|name| name.to_str().ok())
+            .map_or(false, |name| self.root_constant_names.contains(&name));
+
+        if parameter.is_push_constant() || is_named_root_constant {
+            let register_category = if categories.contains(&ParameterCategory::ConstantBuffer) {
+                ParameterCategory::ConstantBuffer
+            } else {
+                ParameterCategory::PushConstantBuffer
+            };
+            let size = type_layout
+                .element_type_layout()
+                .map_or(0, |element| element.size(ParameterCategory::Uniform));
+
+            self.root_constants.push(RootParameter::Constants {
+                shader_register: parameter.offset(register_category) as u32,
+                register_space: (space_offset + parameter.binding_space(register_category)) as u32,
+                num_32bit_values: ((size + 3) / 4) as u32,
+            });
+            return;
+        }
+
+        for category in categories {
+            if category == ParameterCategory::RegisterSpace {
+                // A parameter block: everything inside it lives in its own register space.
+                if let Some(element) = type_layout.element_var_layout() {
+                    let space = space_offset + parameter.offset(ParameterCategory::RegisterSpace);
+                    self.add_parameter(element, space);
+                }
+                continue;
+            }
+
+            let range_type = match DescriptorRangeType::from_category(category) {
+                Some(range_type) => range_type,
+                None => continue,
+            };
+
+            let count = type_layout.size(category);
+            if count == 0 {
+                continue;
+            }
+
+            self.add_range(DescriptorRange {
+                range_type,
+                num_descriptors: if count == crate::UNBOUNDED_SIZE {
+                    DESCRIPTOR_RANGE_UNBOUNDED
+                } else {
+                    count as u32
+                },
+                base_shader_register: parameter.offset(category) as u32,
+                register_space: (space_offset + parameter.binding_space(category)) as u32,
+            });
+        }
+    }
+
+    /// Adds `range` to the table for its register space, unless an identical range is already there.
+    fn add_range(&mut self, range: DescriptorRange) {
+        let tables = if range.range_type == DescriptorRangeType::Sampler {
+            &mut self.sampler_ranges
+        } else {
+            &mut self.resource_ranges
+        };
+        let ranges = tables.entry(range.register_space).or_insert_with(Vec::new);
+        if !ranges.contains(&range) {
+            ranges.push(range);
+        }
+    }
+
+    fn finish(self, flags: RootSignatureFlags) -> RootSignatureDesc {
+        let mut parameters = self.root_constants;
+
+        for mut ranges in self
+            .resource_ranges
+            .into_iter()
+            .chain(self.sampler_ranges)
+            .map(|(_space, ranges)| ranges)
+        {
+            ranges.sort_by_key(|range| (range.range_type, range.base_shader_register));
+            parameters.push(RootParameter::DescriptorTable { ranges });
+        }
+
+        RootSignatureDesc { parameters, flags }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(range_type: DescriptorRangeType, register: u32, space: u32) -> DescriptorRange {
+        DescriptorRange {
+            range_type,
+            num_descriptors: 1,
+            base_shader_register: register,
+            register_space: space,
+        }
+    }
+
+    #[test]
+    fn groups_ranges_into_tables() {
+        let mut collector = Collector::default();
+        collector.root_constants.push(RootParameter::Constants {
+            shader_register: 0,
+            register_space: 0,
+            num_32bit_values: 4,
+        });
+        collector.add_range(range(DescriptorRangeType::Srv, 1, 0));
+        collector.add_range(range(DescriptorRangeType::Sampler, 0, 0));
+        collector.add_range(range(DescriptorRangeType::Cbv, 0, 0));
+        collector.add_range(range(DescriptorRangeType::Srv, 0, 1));
+        // Shared between entry points, so it's reflected twice.
+        collector.add_range(range(DescriptorRangeType::Srv, 1, 0));
+
+        let desc = collector.finish(RootSignatureFlags::empty());
+        assert_eq!(
+            desc.parameters,
+            vec![
+                RootParameter::Constants {
+                    shader_register: 0,
+                    register_space: 0,
+                    num_32bit_values: 4,
+                },
+                RootParameter::DescriptorTable {
+                    ranges: vec![
+                        range(DescriptorRangeType::Cbv, 0, 0),
+                        range(DescriptorRangeType::Srv, 1, 0),
+                    ],
+                },
+                RootParameter::DescriptorTable {
+                    ranges: vec![range(DescriptorRangeType::Srv, 0, 1)],
+                },
+                RootParameter::DescriptorTable {
+                    ranges: vec![range(DescriptorRangeType::Sampler, 0, 0)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_hlsl() {
+        let desc = RootSignatureDesc {
+            parameters: vec![
+                RootParameter::Constants {
+                    shader_register: 2,
+                    register_space: 1,
+                    num_32bit_values: 4,
+                },
+                RootParameter::DescriptorTable {
+                    ranges: vec![
+                        range(DescriptorRangeType::Cbv, 0, 0),
+                        DescriptorRange {
+                            num_descriptors: DESCRIPTOR_RANGE_UNBOUNDED,
+                            ..range(DescriptorRangeType::Uav, 3, 0)
+                        },
+                    ],
+                },
+            ],
+            flags: RootSignatureFlags::ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT,
+        };
+        assert_eq!(
+            desc.to_hlsl(),
+            "RootFlags(ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT), \
+             RootConstants(num32BitConstants=4, b2, space=1), \
+             DescriptorTable(CBV(b0, numDescriptors=1, space=0), UAV(u3, numDescriptors=unbounded, space=0))"
+        );
+        assert_eq!(RootSignatureDesc::default().to_hlsl(), "");
+    }
+}
@@ -0,0 +1,274 @@
+use std::path::PathBuf;
+
+/// Matches Slang's `SlangSeverity`, in increasing order of severity.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticSeverity {
+    Note,
+    Warning,
+    Error,
+    Fatal,
+    /// A bug in Slang rather than in the shader.
+    Internal,
+}
+
+impl DiagnosticSeverity {
+    /// The names Slang prints, longest first so `"fatal error"` isn't read as `"error"`.
+    const NAMES: &'static [(&'static str, DiagnosticSeverity)] = &[
+        ("internal error", DiagnosticSeverity::Internal),
+        ("fatal error", DiagnosticSeverity::Fatal),
+        ("warning", DiagnosticSeverity::Warning),
+        ("error", DiagnosticSeverity::Error),
+        ("note", DiagnosticSeverity::Note),
+    ];
+}
+
+/// One message from Slang's diagnostic output, e.g. `shader.slang(3): error 30015: undefined identifier 'x'.`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    /// Slang's diagnostic number, e.g. 30015. Not every diagnostic has one.
+    pub code: Option<u32>,
+    /// The file the diagnostic is about, as Slang printed it. `None` for diagnostics without a location.
+    pub path: Option<PathBuf>,
+    /// 1-based.
+    pub line: Option<u32>,
+    /// 1-based. Only some Slang versions report columns.
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Parses every diagnostic in Slang's output. The source excerpts Slang prints after a diagnostic, and any other
+    /// lines that aren't diagnostics, are skipped.
+    pub fn parse_all(output: &str) -> Vec<Diagnostic> {
+        output.lines().filter_map(Diagnostic::parse).collect()
+    }
+
+    /// Parses a single line of the form `[path(line[,column]): ]severity[ code]: message`. Text before `): ` is
+    /// only read as a location if what's in the parentheses is a line number, so a message containing `): ` isn't
+    /// split.
+    pub fn parse(line: &str) -> Option<Diagnostic> {
+        let ((path, line_number, column), rest) = line
+            .match_indices("): ")
+            .find_map(|(end, _)| {
+                let start = line[..end].rfind('(')?;
+                let (line_number, column) = parse_position(&line[start + 1..end])?;
+                let path = Some(PathBuf::from(&line[..start])).filter(|_| start > 0);
+                // Slang prints `(0)` for diagnostics without a line.
+                let line_number = Some(line_number).filter(|&line_number| line_number != 0);
+                Some(((path, line_number, column), &line[end + 3..]))
+            })
+            .unwrap_or(((None, None, None), line));
+
+        let (severity, rest) = DiagnosticSeverity::NAMES
+            .iter()
+            .find_map(|&(name, severity)| rest.strip_prefix(name).map(|rest| (severity, rest)))?;
+        let (code, message) = match rest.strip_prefix(':') {
+            Some(message) => (None, message),
+            None => {
+                let rest = rest.strip_prefix(' ')?;
+                let colon = rest.find(':')?;
+                (Some(rest[..colon].parse().ok()?), &rest[colon + 1..])
+            }
+        };
+
+        Some(Diagnostic {
+            severity,
+            code,
+            path,
+            line: line_number,
+            column,
+            message: message.trim().to_string(),
+        })
+    }
+}
+
+/// `line` or `line,column`.
+fn parse_position(position: &str) -> Option<(u32, Option<u32>)> {
+    let mut numbers = position.split(',').map(|number| number.trim().parse().ok());
+    let line = numbers.next()??;
+    let column = match numbers.next() {
+        Some(column) => Some(column?),
+        None => None,
+    };
+    if numbers.next().is_some() {
+        return None;
+    }
+    Some((line, column))
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}", path.display())?;
+        }
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "({},{}): ", line, column)?,
+            (Some(line), None) => write!(f, "({}): ", line)?,
+            _ if self.path.is_some() => write!(f, ": ")?,
+            _ => {}
+        }
+
+        let name = DiagnosticSeverity::NAMES
+            .iter()
+            .find(|(_, severity)| *severity == self.severity)
+            .map_or("error", |(name, _)| *name);
+        match self.code {
+            Some(code) => write!(f, "{} {}: {}", name, code, self.message),
+            None => write!(f, "{}: {}", name, self.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(
+        severity: DiagnosticSeverity,
+        code: Option<u32>,
+        path: Option<&str>,
+        line: Option<u32>,
+        column: Option<u32>,
+        message: &str,
+    ) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code,
+            path: path.map(PathBuf::from),
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_located_diagnostics() {
+        assert_eq!(
+            Diagnostic::parse("shader.slang(3): error 30015: undefined identifier 'x'."),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some(30015),
+                Some("shader.slang"),
+                Some(3),
+                None,
+                "undefined identifier 'x'.",
+            ))
+        );
+        assert_eq!(
+            Diagnostic::parse("shaders/blur.slang(12,7): warning 15205: unused variable"),
+            Some(diagnostic(
+                DiagnosticSeverity::Warning,
+                Some(15205),
+                Some("shaders/blur.slang"),
+                Some(12),
+                Some(7),
+                "unused variable",
+            ))
+        );
+        assert_eq!(
+            Diagnostic::parse(
+                r"C:\Program Files (x86)\shaders\blur.slang(4): note: see declaration"
+            ),
+            Some(diagnostic(
+                DiagnosticSeverity::Note,
+                None,
+                Some(r"C:\Program Files (x86)\shaders\blur.slang"),
+                Some(4),
+                None,
+                "see declaration",
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_diagnostics_without_a_location() {
+        assert_eq!(
+            Diagnostic::parse("error 1: cannot open file 'missing.slang'."),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some(1),
+                None,
+                None,
+                None,
+                "cannot open file 'missing.slang'.",
+            ))
+        );
+        // `(0)` is Slang's way of saying there's no line.
+        assert_eq!(
+            Diagnostic::parse("(0): error 39999: no entry point"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some(39999),
+                None,
+                None,
+                None,
+                "no entry point",
+            ))
+        );
+        assert_eq!(
+            Diagnostic::parse("shader.slang(0): warning: empty file"),
+            Some(diagnostic(
+                DiagnosticSeverity::Warning,
+                None,
+                Some("shader.slang"),
+                None,
+                None,
+                "empty file",
+            ))
+        );
+        // The parentheses don't hold a line number, so this isn't a location.
+        assert_eq!(
+            Diagnostic::parse("error 30019: expected (int): found float"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some(30019),
+                None,
+                None,
+                None,
+                "expected (int): found float",
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_severities() {
+        let severity = |line| Diagnostic::parse(line).map(|diagnostic| diagnostic.severity);
+        assert_eq!(
+            severity("a.slang(1): fatal error 2: too many errors"),
+            Some(DiagnosticSeverity::Fatal)
+        );
+        assert_eq!(
+            severity("internal error 99999: unimplemented"),
+            Some(DiagnosticSeverity::Internal)
+        );
+        assert_eq!(
+            severity("a.slang(1): note: here"),
+            Some(DiagnosticSeverity::Note)
+        );
+    }
+
+    #[test]
+    fn skips_other_lines() {
+        assert_eq!(Diagnostic::parse("    float x = y;"), None);
+        assert_eq!(Diagnostic::parse("              ^"), None);
+        assert_eq!(Diagnostic::parse("a.slang(1): error abc: not a code"), None);
+        assert_eq!(Diagnostic::parse(""), None);
+        assert_eq!(
+            Diagnostic::parse_all("a.slang(2): error 3: bad\n    x;\n    ^\nnote: more\n").len(),
+            2
+        );
+    }
+
+    #[test]
+    fn display_matches_slang() {
+        for line in &[
+            "shader.slang(3): error 30015: undefined identifier 'x'.",
+            "shader.slang(12,7): warning 15205: unused variable",
+            "fatal error 2: too many errors",
+            "internal error: unimplemented",
+        ] {
+            assert_eq!(Diagnostic::parse(line).unwrap().to_string(), *line);
+        }
+    }
+}
@@ -3,35 +3,100 @@
 // #![warn(clippy::pedantic)]
 
 use slang_sys::*;
+use std::cell::RefCell;
 use std::ffi::CStr;
+use std::marker::PhantomData;
 use std::num::NonZeroU32;
+use std::path::Path;
 use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-mod enums;
 #[macro_use]
 mod macros;
+
+pub mod batch;
+mod blob;
+mod builder;
+pub mod cache;
+pub mod capabilities;
+pub mod d3d12;
+mod diagnostic;
+mod enums;
+pub mod permutation;
+mod pool;
+mod profile;
+mod reflection;
 mod result;
+mod string;
+pub mod watch;
 
+pub use blob::Blob;
+pub use builder::*;
+pub use diagnostic::{Diagnostic, DiagnosticSeverity};
 pub use enums::*;
+pub use pool::{PooledSession, SessionPool};
+pub use profile::Profile;
+pub use reflection::*;
 pub use slang_sys as ffi;
 
 use result::into_result;
+pub use result::BuilderError;
+pub use result::CompileError;
 pub use result::Error;
 pub use result::Result;
+pub use result::SlangResult;
+pub use result::SpecializationError;
+pub use string::SlangStr;
 
-lifetime_wrapper_struct!(Session, *mut SlangSession);
+/// Identifies the version of Slang this crate is linked against, e.g. `"v0.10.24"`.
+pub fn get_build_tag_string() -> &'static CStr {
+    unsafe { CStr::from_ptr(spGetBuildTagString()) }
+}
 
-impl Default for Session<'_> {
+/// Owns a `*mut SlangSession`.
+///
+/// A session can be moved to another thread, but Slang doesn't allow one session to be used from several threads
+/// at once, so it's `Send` but not `Sync`. Compile requests borrow their session and stay on its thread. Use a
+/// `SessionPool` to compile on several threads in parallel.
+pub struct Session {
+    inner: *mut SlangSession,
+}
+
+// Slang sessions have no thread affinity; they only require that calls into a session (and the requests created
+// from it) aren't made concurrently, which `!Sync` and the request's borrow guarantee.
+unsafe impl Send for Session {}
+
+impl Default for Session {
     fn default() -> Self {
-        unsafe { spCreateSession(ptr::null()).into() }
+        unsafe { Self::from_raw(spCreateSession(ptr::null())) }
     }
 }
 
-impl Session<'_> {
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("Session").field(&self.inner).finish()
+    }
+}
+
+impl Session {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Takes ownership of `inner`, which is destroyed when the `Session` is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must be a valid session that isn't owned by anything else.
+    pub unsafe fn from_raw(inner: *mut SlangSession) -> Self {
+        Self { inner }
+    }
+
+    /// Retreives the inner value.
+    pub fn get(&self) -> *mut SlangSession {
+        self.inner
+    }
+
     // pub fn set_shared_library_loader(&self, loader: ISlangSharedLibraryLoader) {
     //     unimplemented!()
     // }
@@ -40,7 +105,8 @@ impl Session<'_> {
     //     unimplemented!()
     // }
 
-    // bool?
+    /// Fails if Slang can't generate code for `target`, e.g. because the downstream compiler it needs can't be
+    /// loaded. See `capabilities` for every target at once.
     pub fn check_compile_target_support(&self, target: CompileTarget) -> Result<()> {
         unsafe {
             into_result(spSessionCheckCompileTargetSupport(
@@ -50,7 +116,7 @@ impl Session<'_> {
         }
     }
 
-    // bool?
+    /// Fails if the downstream compiler can't be loaded.
     pub fn check_pass_through_support(&self, pass_through: PassThrough) -> Result<()> {
         unsafe {
             into_result(spSessionCheckPassThroughSupport(
@@ -60,29 +126,48 @@ impl Session<'_> {
         }
     }
 
-    pub fn add_builtins(&self, source_path: impl AsRef<CStr>, source_string: impl AsRef<CStr>) {
+    /// Checks every target and downstream compiler.
+    pub fn capabilities(&self) -> capabilities::Capabilities {
+        capabilities::Capabilities::probe(self)
+    }
+
+    pub fn add_builtins(
+        &self,
+        source_path: impl SlangStr,
+        source_string: impl SlangStr,
+    ) -> Result<()> {
+        let source_path = source_path.to_slang_str()?;
+        let source_string = source_string.to_slang_str()?;
         unsafe {
-            spAddBuiltins(
-                self.get(),
-                source_path.as_ref().as_ptr(),
-                source_string.as_ref().as_ptr(),
-            )
+            spAddBuiltins(self.get(), source_path.as_ptr(), source_string.as_ptr());
         }
+        Ok(())
     }
 
-    pub fn create_compile_request(&self) -> CompileRequest {
-        unsafe { spCreateCompileRequest(self.get()).into() }
+    /// The request borrows the session, since it can't outlive it.
+    pub fn create_compile_request(&self) -> CompileRequest<'_> {
+        unsafe { CompileRequest::from_raw(spCreateCompileRequest(self.get())) }
     }
 
-    pub fn find_profile(&self, name: impl AsRef<CStr>) -> Option<ProfileId> {
+    /// Returns `None` for unknown profiles. Takes a name or a `Profile`.
+    pub fn find_profile(&self, name: impl SlangStr) -> Result<Option<ProfileId>> {
+        let name = name.to_slang_str()?;
         unsafe {
-            let profile = spFindProfile(self.get(), name.as_ref().as_ptr());
-            NonZeroU32::new(profile)
+            let profile = spFindProfile(self.get(), name.as_ptr());
+            Ok(NonZeroU32::new(profile))
         }
     }
+
+    /// Every profile in `Profile::known` that this version of Slang recognizes.
+    pub fn profiles(&self) -> Vec<Profile> {
+        Profile::known()
+            .into_iter()
+            .filter(|profile| matches!(self.find_profile(profile), Ok(Some(_))))
+            .collect()
+    }
 }
 
-impl Drop for Session<'_> {
+impl Drop for Session {
     fn drop(&mut self) {
         unsafe {
             spDestroySession(self.get());
@@ -95,14 +180,130 @@ type DiagnosticCallback = unsafe extern "C" fn(
     user_data: *mut ::std::os::raw::c_void,
 );
 
-lifetime_wrapper_struct_copy!(CodeGenTarget, i32);
-lifetime_wrapper_struct_copy!(TranslationUnitIndex, i32);
-lifetime_wrapper_struct_copy!(EntryPointIndex, i32);
+request_index_struct!(CodeGenTarget);
+request_index_struct!(TranslationUnitIndex);
+request_index_struct!(EntryPointIndex);
+
+/// Hands out the ids that tie indices to the request that returned them.
+static NEXT_REQUEST_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Owns a `*mut SlangCompileRequest` created from the `Session` it borrows.
+///
+/// Outputs like `get_diagnostic_output` and `get_reflection` borrow the request, and `compile` takes `&mut self`,
+/// so outputs can't be used after the request is dropped or recompiled.
+pub struct CompileRequest<'s> {
+    inner: *mut SlangCompileRequest,
+    /// Unique among requests, see `NEXT_REQUEST_ID`.
+    id: usize,
+    indices: RefCell<RequestIndices>,
+    phantom: PhantomData<&'s Session>,
+}
+
+/// The indices Slang returned when things were added to a request, in the order they were added. Slang doesn't
+/// check the indices it's given, so every index is checked before it's passed on: it has to carry the request's id,
+/// and still be one of these, which `set_code_gen_target` replaces.
+#[derive(Debug, Default)]
+struct RequestIndices {
+    code_gen_targets: Vec<i32>,
+    translation_units: Vec<i32>,
+    entry_points: Vec<i32>,
+}
+
+impl RequestIndices {
+    fn check(
+        indices: &[i32],
+        request_id: usize,
+        kind: &'static str,
+        index: i32,
+        index_request_id: usize,
+    ) -> Result<i32> {
+        if index_request_id == request_id && indices.contains(&index) {
+            Ok(index)
+        } else {
+            Err(Error::UnknownIndex { kind, index })
+        }
+    }
+}
+
+impl std::fmt::Debug for CompileRequest<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("CompileRequest").field(&self.inner).finish()
+    }
+}
+
+/// See `CompileRequestBuilder` for a higher level way of setting up and compiling a request.
+impl<'s> CompileRequest<'s> {
+    /// Takes ownership of `inner`, which is destroyed when the `CompileRequest` is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must be a valid request that isn't owned by anything else, created from a session that outlives
+    /// `'s`.
+    pub unsafe fn from_raw(inner: *mut SlangCompileRequest) -> Self {
+        Self {
+            inner,
+            id: NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed),
+            indices: RefCell::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Retreives the inner value.
+    pub fn get(&self) -> *mut SlangCompileRequest {
+        self.inner
+    }
 
-lifetime_wrapper_struct!(CompileRequest, *mut SlangCompileRequest);
+    /// Every target added with `add_code_gen_target`, in the order they were added.
+    pub fn code_gen_targets(&self) -> Vec<CodeGenTarget<'s>> {
+        self.indices
+            .borrow()
+            .code_gen_targets
+            .iter()
+            .map(|&index| CodeGenTarget::wrap(index, self.id))
+            .collect()
+    }
+
+    fn code_gen_target(&self, target: CodeGenTarget) -> Result<i32> {
+        RequestIndices::check(
+            &self.indices.borrow().code_gen_targets,
+            self.id,
+            "code gen target",
+            target.get(),
+            target.request_id(),
+        )
+    }
+
+    fn translation_unit(&self, translation_unit: TranslationUnitIndex) -> Result<i32> {
+        RequestIndices::check(
+            &self.indices.borrow().translation_units,
+            self.id,
+            "translation unit",
+            translation_unit.get(),
+            translation_unit.request_id(),
+        )
+    }
+
+    fn entry_point(&self, entry_point: EntryPointIndex) -> Result<i32> {
+        RequestIndices::check(
+            &self.indices.borrow().entry_points,
+            self.id,
+            "entry point",
+            entry_point.get(),
+            entry_point.request_id(),
+        )
+    }
+
+    /// Every entry point added with `add_entry_point` and the other `add_*_entry_point*` methods, in the order they
+    /// were added.
+    pub fn entry_points(&self) -> Vec<EntryPointIndex<'s>> {
+        self.indices
+            .borrow()
+            .entry_points
+            .iter()
+            .map(|&index| EntryPointIndex::wrap(index, self.id))
+            .collect()
+    }
 
-// TODO there's got to be a better API shape that helps inform of usage... builder pattern maybe?
-impl<'a> CompileRequest<'a> {
     // pub fn set_file_system(&self, ISlangFileSystem* fileSystem) {}
 
     pub fn set_compile_flags(&self, flags: CompileFlags) {
@@ -117,52 +318,80 @@ impl<'a> CompileRequest<'a> {
         }
     }
 
+    /// Where `set_dump_intermediates` writes files, which is otherwise the current directory. Slang appends a
+    /// number and extension to `prefix`, so a directory path ending in a separator puts them in that directory.
+    pub fn set_dump_intermediate_prefix(&self, prefix: impl SlangStr) -> Result<()> {
+        let prefix = prefix.to_slang_str()?;
+        unsafe {
+            spSetDumpIntermediatePrefix(self.get(), prefix.as_ptr());
+        }
+        Ok(())
+    }
+
     pub fn set_line_directive_mode(&self, mode: LineDirectiveMode) {
         unsafe {
             spSetLineDirectiveMode(self.get(), mode as u32);
         }
     }
 
-    pub fn set_code_gen_target(&self, target: CodeGenTarget) {
+    /// Replaces every target added so far with `target`, which Slang puts at index 0, and returns it. Other
+    /// indices returned by `add_code_gen_target` can't be used afterwards.
+    pub fn set_code_gen_target(&self, target: CompileTarget) -> CodeGenTarget<'s> {
         unsafe {
-            spSetCodeGenTarget(self.get(), target.get());
+            spSetCodeGenTarget(self.get(), target as SlangCompileTarget);
         }
+        self.indices.borrow_mut().code_gen_targets = vec![0];
+        CodeGenTarget::wrap(0, self.id)
     }
 
-    pub fn add_code_gen_target(&self, target: CompileTarget) -> CodeGenTarget {
-        unsafe { spAddCodeGenTarget(self.get(), target as SlangCompileTarget).into() }
+    pub fn add_code_gen_target(&self, target: CompileTarget) -> CodeGenTarget<'s> {
+        let index = unsafe { spAddCodeGenTarget(self.get(), target as SlangCompileTarget) };
+        self.indices.borrow_mut().code_gen_targets.push(index);
+        CodeGenTarget::wrap(index, self.id)
     }
 
-    pub fn set_target_profile(&self, target_index: CodeGenTarget, profile: ProfileId) {
+    pub fn set_target_profile(
+        &self,
+        target_index: CodeGenTarget,
+        profile: ProfileId,
+    ) -> Result<()> {
+        let target_index = self.code_gen_target(target_index)?;
         unsafe {
-            spSetTargetProfile(self.get(), target_index.get(), profile.get());
+            spSetTargetProfile(self.get(), target_index, profile.get());
         }
+        Ok(())
     }
 
-    pub fn set_target_flags(&self, target_index: CodeGenTarget, flags: TargetFlags) {
+    pub fn set_target_flags(&self, target_index: CodeGenTarget, flags: TargetFlags) -> Result<()> {
+        let target_index = self.code_gen_target(target_index)?;
         unsafe {
-            spSetTargetFlags(self.get(), target_index.get(), flags.bits() as u32);
+            spSetTargetFlags(self.get(), target_index, flags.bits() as u32);
         }
+        Ok(())
     }
 
     pub fn set_target_floating_point_mode(
         &self,
         target_index: CodeGenTarget,
         mode: FloatingPointMode,
-    ) {
+    ) -> Result<()> {
+        let target_index = self.code_gen_target(target_index)?;
         unsafe {
-            spSetTargetFloatingPointMode(self.get(), target_index.get(), mode as u32);
+            spSetTargetFloatingPointMode(self.get(), target_index, mode as u32);
         }
+        Ok(())
     }
 
     pub fn set_target_matrix_layout_mode(
         &self,
         target_index: CodeGenTarget,
         mode: MatrixLayoutMode,
-    ) {
+    ) -> Result<()> {
+        let target_index = self.code_gen_target(target_index)?;
         unsafe {
-            spSetTargetMatrixLayoutMode(self.get(), target_index.get(), mode as u32);
+            spSetTargetMatrixLayoutMode(self.get(), target_index, mode as u32);
         }
+        Ok(())
     }
 
     pub fn set_matrix_layout_mode(&self, mode: MatrixLayoutMode) {
@@ -171,6 +400,19 @@ impl<'a> CompileRequest<'a> {
         }
     }
 
+    /// How much debug information, like source line mappings, to include in the output.
+    pub fn set_debug_info_level(&self, level: DebugInfoLevel) {
+        unsafe {
+            spSetDebugInfoLevel(self.get(), level as SlangDebugInfoLevel);
+        }
+    }
+
+    pub fn set_optimization_level(&self, level: OptimizationLevel) {
+        unsafe {
+            spSetOptimizationLevel(self.get(), level as SlangOptimizationLevel);
+        }
+    }
+
     pub fn set_output_container_format(&self, format: ContainerFormat) {
         unsafe {
             spSetOutputContainerFormat(self.get(), format as i32);
@@ -184,6 +426,10 @@ impl<'a> CompileRequest<'a> {
     }
 
     // TODO: is there a better way of getting a callback passed in? thread local `unsafe extern "c"` that rust assigns to before each call?
+    /// # Safety
+    ///
+    /// `callback` may be called with `user_data` until the request is dropped, so `user_data` must stay valid for
+    /// at least that long.
     pub unsafe fn set_diagnostic_callback(
         &self,
         callback: DiagnosticCallback,
@@ -195,122 +441,272 @@ impl<'a> CompileRequest<'a> {
     // pub fn set_writer(&self,  channel:WriterChannel, ISlangWriter* writer) {unsafe { spSetWriter(self.get(),  channel:WriterChannel, ISlangWriter* writer); } }
     // pub fn get_writer(&self,  channel:WriterChannel) -> ISlangWriter*  {unsafe { spGetWriter(self.get(),  channel:WriterChannel) -> ISlangWriter* ; } }
 
-    pub fn add_search_path(&self, search_dir: impl AsRef<CStr>) {
+    pub fn add_search_path(&self, search_dir: impl SlangStr) -> Result<()> {
+        let search_dir = search_dir.to_slang_str()?;
         unsafe {
-            spAddSearchPath(self.get(), search_dir.as_ref().as_ptr());
+            spAddSearchPath(self.get(), search_dir.as_ptr());
         }
+        Ok(())
     }
 
-    pub fn add_preprocessor_define(&self, key: impl AsRef<CStr>, value: impl AsRef<CStr>) {
+    pub fn add_preprocessor_define(&self, key: impl SlangStr, value: impl SlangStr) -> Result<()> {
+        let key = key.to_slang_str()?;
+        let value = value.to_slang_str()?;
         unsafe {
-            spAddPreprocessorDefine(self.get(), key.as_ref().as_ptr(), value.as_ref().as_ptr());
+            spAddPreprocessorDefine(self.get(), key.as_ptr(), value.as_ptr());
         }
+        Ok(())
+    }
+
+    /// Applies `slangc` options to the request, e.g. `&["-profile", "sm_5_0", "-D", "FOO=1"]`. Each option and value
+    /// is its own argument, as if the string had been split by a shell.
+    ///
+    /// Fails with `Error::CommandLine`, carrying Slang's diagnostics, if an option isn't recognized or is missing
+    /// its value.
+    pub fn process_command_line_arguments<S: SlangStr>(&self, args: &[S]) -> Result<()> {
+        let args = args
+            .iter()
+            .map(|a| a.to_slang_str())
+            .collect::<Result<Vec<_>>>()?;
+        let arg_ptrs = args.iter().map(|a| a.as_ptr()).collect::<Vec<_>>();
+
+        let result = unsafe {
+            into_result(spProcessCommandLineArguments(
+                self.get(),
+                arg_ptrs.as_ptr(),
+                arg_ptrs.len() as i32,
+            ))
+        };
+        result.map_err(|error| Error::CommandLine {
+            error: Box::new(error),
+            diagnostics: self.get_diagnostic_output().to_string_lossy().into_owned(),
+        })
     }
 
-    // pub fn process_command_line_arguments(&self, cstr const* args, int argCount) -> SlangResult  { unsafe { spProcessCommandLineArguments(self.get(), cstr const* args, int argCount) } }
+    /// Makes the declarations in a Slang module, as returned by `get_compile_request_code` with
+    /// `ContainerFormat::ContainerFormatSlangModule`, available to this request without recompiling its source.
+    /// Slang copies what it needs from `module` before returning.
+    pub fn add_library_reference(&self, module: &[u8]) -> Result<()> {
+        unsafe {
+            into_result(spAddLibraryReference(
+                self.get(),
+                module.as_ptr() as *const _,
+                module.len(),
+            ))
+        }
+    }
 
     pub fn add_translation_unit(
         &self,
         language: SourceLanguage,
-        name: impl AsRef<CStr>,
-    ) -> TranslationUnitIndex {
-        unsafe {
-            spAddTranslationUnit(
-                self.get(),
-                language as SlangSourceLanguage,
-                name.as_ref().as_ptr(),
-            )
-            .into()
-        }
+        name: impl SlangStr,
+    ) -> Result<TranslationUnitIndex<'s>> {
+        let name = name.to_slang_str()?;
+        let index = unsafe {
+            spAddTranslationUnit(self.get(), language as SlangSourceLanguage, name.as_ptr())
+        };
+        self.indices.borrow_mut().translation_units.push(index);
+        Ok(TranslationUnitIndex::wrap(index, self.id))
     }
 
     pub fn translation_unit_add_preprocessor_define(
         &self,
         translation_unit_index: TranslationUnitIndex,
-        key: impl AsRef<CStr>,
-        value: impl AsRef<CStr>,
-    ) {
+        key: impl SlangStr,
+        value: impl SlangStr,
+    ) -> Result<()> {
+        let translation_unit_index = self.translation_unit(translation_unit_index)?;
+        let key = key.to_slang_str()?;
+        let value = value.to_slang_str()?;
         unsafe {
             spTranslationUnit_addPreprocessorDefine(
                 self.get(),
-                translation_unit_index.get(),
-                key.as_ref().as_ptr(),
-                value.as_ref().as_ptr(),
+                translation_unit_index,
+                key.as_ptr(),
+                value.as_ptr(),
             );
         }
+        Ok(())
     }
 
-    // pub fn add_translation_unit_source_file(&self, translation_unit_index: TranslationUnitIndex, cstr path) {}
+    /// Slang reads the file when the request is compiled, resolving includes relative to it.
+    pub fn add_translation_unit_source_file(
+        &self,
+        translation_unit_index: TranslationUnitIndex,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let translation_unit_index = self.translation_unit(translation_unit_index)?;
+        let path = path.as_ref().to_slang_str()?;
+        unsafe {
+            spAddTranslationUnitSourceFile(self.get(), translation_unit_index, path.as_ptr());
+        }
+        Ok(())
+    }
 
     pub fn add_translation_unit_source_string(
         &self,
         translation_unit_index: TranslationUnitIndex,
-        path: impl AsRef<CStr>,
-        source: impl AsRef<CStr>,
-    ) {
+        path: impl SlangStr,
+        source: impl SlangStr,
+    ) -> Result<()> {
+        let translation_unit_index = self.translation_unit(translation_unit_index)?;
+        let path = path.to_slang_str()?;
+        let source = source.to_slang_str()?;
         unsafe {
             spAddTranslationUnitSourceString(
                 self.get(),
-                translation_unit_index.get(),
-                path.as_ref().as_ptr(),
-                source.as_ref().as_ptr(),
+                translation_unit_index,
+                path.as_ptr(),
+                source.as_ptr(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Like `add_translation_unit_source_string`, but `source` doesn't need to be NUL-terminated, so it's passed
+    /// without a copy on the Rust side.
+    pub fn add_translation_unit_source_string_span(
+        &self,
+        translation_unit_index: TranslationUnitIndex,
+        path: impl SlangStr,
+        source: &[u8],
+    ) -> Result<()> {
+        let translation_unit_index = self.translation_unit(translation_unit_index)?;
+        let path = path.to_slang_str()?;
+        let range = source.as_ptr_range();
+        unsafe {
+            spAddTranslationUnitSourceStringSpan(
+                self.get(),
+                translation_unit_index,
+                path.as_ptr(),
+                range.start as *const _,
+                range.end as *const _,
             );
         }
+        Ok(())
     }
 
-    // pub fn add_translation_unit_source_string_span(&self, translation_unit_index: TranslationUnitIndex, cstr path, cstr sourceBegin, cstr sourceEnd) {}
-    // pub fn add_translation_unit_source_blob(&self, translation_unit_index: TranslationUnitIndex, cstr path, ISlangBlob* sourceBlob) {}
+    /// Slang keeps its own reference to `source`, so it isn't copied.
+    pub fn add_translation_unit_source_blob(
+        &self,
+        translation_unit_index: TranslationUnitIndex,
+        path: impl SlangStr,
+        source: &Blob,
+    ) -> Result<()> {
+        let translation_unit_index = self.translation_unit(translation_unit_index)?;
+        let path = path.to_slang_str()?;
+        unsafe {
+            spAddTranslationUnitSourceBlob(
+                self.get(),
+                translation_unit_index,
+                path.as_ptr(),
+                source.get(),
+            );
+        }
+        Ok(())
+    }
 
     pub fn add_entry_point(
         &self,
         translation_unit_index: TranslationUnitIndex,
-        name: impl AsRef<CStr>,
+        name: impl SlangStr,
         stage: Stage,
-    ) -> EntryPointIndex {
-        unsafe {
+    ) -> Result<EntryPointIndex<'s>> {
+        let translation_unit_index = self.translation_unit(translation_unit_index)?;
+        let name = name.to_slang_str()?;
+        let index = unsafe {
             spAddEntryPoint(
                 self.get(),
-                translation_unit_index.get(),
-                name.as_ref().as_ptr(),
+                translation_unit_index,
+                name.as_ptr(),
                 stage as SlangStage,
             )
-            .into()
-        }
+        };
+        self.indices.borrow_mut().entry_points.push(index);
+        Ok(EntryPointIndex::wrap(index, self.id))
     }
 
-    pub fn add_entry_point_ex(
+    pub fn add_entry_point_ex<S: SlangStr>(
         &self,
         translation_unit_index: TranslationUnitIndex,
-        name: impl AsRef<CStr>,
+        name: impl SlangStr,
         stage: Stage,
-        generic_type_names: &[&CStr],
-    ) -> EntryPointIndex {
+        generic_type_names: &[S],
+    ) -> Result<EntryPointIndex<'s>> {
+        let translation_unit_index = self.translation_unit(translation_unit_index)?;
+        let name = name.to_slang_str()?;
+        let generic_type_names = generic_type_names
+            .iter()
+            .map(|a| a.to_slang_str())
+            .collect::<Result<Vec<_>>>()?;
+        let mut generic_type_names_ptrs = generic_type_names
+            .iter()
+            .map(|a| a.as_ptr())
+            .collect::<Vec<_>>();
+
+        let index = unsafe {
+            spAddEntryPointEx(
+                self.get(),
+                translation_unit_index,
+                name.as_ptr(),
+                stage as SlangStage,
+                generic_type_names_ptrs.len() as i32,
+                generic_type_names_ptrs.as_mut_ptr(),
+            )
+        };
+        self.indices.borrow_mut().entry_points.push(index);
+        Ok(EntryPointIndex::wrap(index, self.id))
+    }
+
+    /// Adds a generic entry point specialized with `type_args`, e.g. types found with
+    /// `ShaderReflection::find_type_arguments`.
+    pub fn add_specialized_entry_point(
+        &self,
+        translation_unit_index: TranslationUnitIndex,
+        name: impl SlangStr,
+        stage: Stage,
+        type_args: &[TypeArgument],
+    ) -> Result<EntryPointIndex<'s>> {
+        self.add_entry_point_ex(translation_unit_index, name, stage, type_args)
+    }
+
+    /// Specializes the global generic type parameters (`type_param T : IFoo;`) with `type_args`, in declaration order.
+    pub fn set_global_generic_args(&self, type_args: &[TypeArgument]) -> Result<()> {
         unsafe {
-            let mut generic_type_names_ptrs = generic_type_names
+            let mut type_names_ptrs = type_args
                 .iter()
-                .map(|a| a.as_ptr())
+                .map(|arg| arg.name().as_ptr())
                 .collect::<Vec<_>>();
 
-            dbg!(&generic_type_names);
-            dbg!(&generic_type_names_ptrs);
-
-            spAddEntryPointEx(
+            into_result(spSetGlobalGenericArgs(
                 self.get(),
-                translation_unit_index.get(),
-                name.as_ref().as_ptr(),
-                stage as SlangStage,
-                generic_type_names.len() as i32,
-                &mut generic_type_names_ptrs[0],
-            )
-            .into()
+                type_names_ptrs.len() as i32,
+                type_names_ptrs.as_mut_ptr(),
+            ))
         }
     }
 
-    pub fn compile(&self) -> Result<()> {
-        unsafe { into_result(spCompile(self.get())) }
+    /// Fails with `Error::Specialization` if a type argument doesn't conform to the interface its generic parameter
+    /// is constrained to, and otherwise with `Error::Compile` carrying Slang's diagnostics.
+    ///
+    /// Takes `&mut self` since compiling invalidates previously retrieved outputs.
+    pub fn compile(&mut self) -> Result<()> {
+        let result = SlangResult(unsafe { spCompile(self.get()) });
+        if result.is_ok() {
+            return Ok(());
+        }
+
+        let error = CompileError::new(
+            result,
+            self.get_diagnostic_output().to_string_lossy().into_owned(),
+        );
+        match SpecializationError::from_diagnostics(&error.diagnostics) {
+            Some(specialization_error) => Err(Error::Specialization(specialization_error)),
+            None => Err(Error::Compile(error)),
+        }
     }
 
-    pub fn get_diagnostic_output(&self) -> &'a CStr {
+    pub fn get_diagnostic_output(&self) -> &CStr {
         unsafe { CStr::from_ptr(spGetDiagnosticOutput(self.get())) }
     }
 
@@ -319,7 +715,7 @@ impl<'a> CompileRequest<'a> {
         unsafe { spGetDependencyFileCount(self.get()) as usize }
     }
 
-    pub fn get_dependency_file_path(&self, index: usize) -> &'a CStr {
+    pub fn get_dependency_file_path(&self, index: usize) -> &CStr {
         unsafe { CStr::from_ptr(spGetDependencyFilePath(self.get(), index as i32)) }
     }
 
@@ -327,26 +723,87 @@ impl<'a> CompileRequest<'a> {
         unsafe { spGetTranslationUnitCount(self.get()) as usize }
     }
 
-    pub fn get_entry_point_source(&self, entry_point_index: EntryPointIndex) -> &'a CStr {
-        unsafe { CStr::from_ptr(spGetEntryPointSource(self.get(), entry_point_index.get())) }
+    /// Fails with `Error::UnknownIndex` if `entry_point_index` wasn't returned by this request.
+    pub fn get_entry_point_source(&self, entry_point_index: EntryPointIndex) -> Result<&CStr> {
+        let entry_point_index = self.entry_point(entry_point_index)?;
+        unsafe {
+            Ok(CStr::from_ptr(spGetEntryPointSource(
+                self.get(),
+                entry_point_index,
+            )))
+        }
     }
 
-    pub fn get_entry_point_code(&self, entry_point_index: EntryPointIndex) -> &'a [u8] {
+    /// The code for the first target. Fails with `Error::UnknownIndex` if `entry_point_index` wasn't returned by this
+    /// request.
+    pub fn get_entry_point_code(&self, entry_point_index: EntryPointIndex) -> Result<&[u8]> {
+        let entry_point_index = self.entry_point(entry_point_index)?;
         unsafe {
             let mut out_size: usize = 0;
-            let blob = spGetEntryPointCode(
+            let blob =
+                spGetEntryPointCode(self.get(), entry_point_index, &mut out_size as *mut usize);
+
+            if blob.is_null() {
+                Ok(&[])
+            } else {
+                Ok(std::slice::from_raw_parts(blob as *const u8, out_size))
+            }
+        }
+    }
+
+    /// Like `get_entry_point_code`, but for any of the request's targets.
+    pub fn get_entry_point_code_blob(
+        &self,
+        entry_point_index: EntryPointIndex,
+        target_index: CodeGenTarget,
+    ) -> Result<Blob> {
+        let entry_point_index = self.entry_point(entry_point_index)?;
+        let target_index = self.code_gen_target(target_index)?;
+        unsafe {
+            let mut blob = ptr::null_mut();
+            into_result(spGetEntryPointCodeBlob(
                 self.get(),
-                entry_point_index.get(),
-                &mut out_size as *mut usize,
-            );
+                entry_point_index,
+                target_index,
+                &mut blob,
+            ))?;
+            Ok(Blob::from_raw(blob))
+        }
+    }
 
-            std::slice::from_raw_parts(blob as *const u8, out_size)
+    /// The whole request's output in the container format set with `set_output_container_format`, e.g. a Slang
+    /// module that can be loaded into another request with `add_library_reference`. Empty if no container was
+    /// produced.
+    pub fn get_compile_request_code(&self) -> &[u8] {
+        unsafe {
+            let mut out_size: usize = 0;
+            let data = spGetCompileRequestCode(self.get(), &mut out_size as *mut usize);
+
+            if data.is_null() {
+                &[]
+            } else {
+                std::slice::from_raw_parts(data as *const u8, out_size)
+            }
+        }
+    }
+
+    /// Returns `None` if the request hasn't been successfully compiled.
+    pub fn get_reflection(&self) -> Option<ShaderReflection<'_>> {
+        unsafe {
+            let reflection = spGetReflection(self.get());
+            if reflection.is_null() {
+                None
+            } else {
+                Some(ShaderReflection::wrap(reflection))
+            }
         }
     }
 
-    // pub fn get_entry_point_code_blob(&self,  entry_point_index: EntryPointIndex, targetIndex: CodeGenTarget, ISlangBlob** outBlob) -> SlangResult  {}
-    // pub fn get_compile_request_code(&self, size_t* outSize) -> void const*  {}
-    // pub fn get_reflection(&self) -> SlangReflection*  {}
+    /// The push constant range across all entry points in the request. See `ShaderReflection::push_constant_range`.
+    pub fn get_push_constant_range(&self) -> Option<PushConstantRange> {
+        self.get_reflection()
+            .and_then(|reflection| reflection.push_constant_range())
+    }
 }
 
 impl Drop for CompileRequest<'_> {
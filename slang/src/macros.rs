@@ -6,7 +6,7 @@ macro_rules! doc_comment {
     };
 }
 
-/// Implementation details for `lifetime_wrapper_struct`.
+/// Implementation details for `lifetime_wrapper_struct_copy`.
 macro_rules! lifetime_wrapper_struct_impl {
     ($wrapper_name:ident, $inner:ty) => {
         impl ::std::fmt::Debug for $wrapper_name<'_> {
@@ -17,11 +17,13 @@ macro_rules! lifetime_wrapper_struct_impl {
             }
         }
 
-        /// Autogenerated by `lifetime_wrapper_struct`.
+        /// Autogenerated by `lifetime_wrapper_struct_copy`.
         impl<'a> $wrapper_name<'a> {
             /// Creates a wrapper from `inner`.
+            ///
+            /// Crate-private so safe code outside the crate can't attach an arbitrary lifetime to a handle.
             #[inline]
-            pub fn wrap(inner: $inner) -> Self {
+            pub(crate) fn wrap(inner: $inner) -> Self {
                 Self {
                     inner,
                     phantom: ::std::marker::PhantomData,
@@ -34,25 +36,17 @@ macro_rules! lifetime_wrapper_struct_impl {
                 self.inner
             }
         }
-
-        doc_comment! {
-            concat!("Helper to call `", stringify!($wrapper_name), "::wrap(", stringify!($inner), ")`."),
-            impl From<$inner> for $wrapper_name<'_> {
-                fn from(inner: $inner) -> Self {
-                    Self::wrap(inner)
-                }
-            }
-        }
     };
 }
 
-/// Generate a wrapper struct with a lifetime specifier.
+/// Generate a `Copy` wrapper struct with a lifetime specifier, for handles that don't own what they point to.
 ///
 /// Use `$wrapper_name::wrap($inner)` and `$wrapper_name::get() -> $inner` to wrap and access the value.
-macro_rules! lifetime_wrapper_struct {
+macro_rules! lifetime_wrapper_struct_copy {
     ($wrapper_name:ident, $inner:ty) => {
         doc_comment! {
             concat!("Wraps `", stringify!($inner), "` with a lifetime specifier."),
+            #[derive(Copy, Clone, PartialEq, Eq, Hash)]
             pub struct $wrapper_name<'a> {
                 inner: $inner,
                 phantom: ::std::marker::PhantomData<&'a $inner>,
@@ -62,17 +56,140 @@ macro_rules! lifetime_wrapper_struct {
     };
 }
 
-/// `lifetime_wrapper_struct` for `Copy` types.
-macro_rules! lifetime_wrapper_struct_copy {
-    ($wrapper_name:ident, $inner:ty) => {
-        doc_comment! {
-            concat!("Wraps `", stringify!($inner), "` with a lifetime specifier."),
-            #[derive(Copy, Clone, PartialEq, Eq, Hash)]
-            pub struct $wrapper_name<'a> {
-                inner: $inner,
-                phantom: ::std::marker::PhantomData<&'a $inner>,
+/// Generate a `Copy` index into a `CompileRequest`, tagged with the id of the request that returned it so that it
+/// can't be used with another one.
+///
+/// Use `$wrapper_name::wrap(index, request_id)` and `$wrapper_name::get() -> i32` to wrap and access the index.
+macro_rules! request_index_struct {
+    ($wrapper_name:ident) => {
+        /// An index returned by a `CompileRequest`, which only that request accepts.
+        #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+        pub struct $wrapper_name<'a> {
+            inner: i32,
+            request_id: usize,
+            phantom: ::std::marker::PhantomData<&'a i32>,
+        }
+
+        impl ::std::fmt::Debug for $wrapper_name<'_> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.debug_tuple(stringify!($wrapper_name))
+                    .field(&self.inner)
+                    .finish()
+            }
+        }
+
+        /// Autogenerated by `request_index_struct`.
+        impl<'a> $wrapper_name<'a> {
+            #[inline]
+            pub(crate) fn wrap(inner: i32, request_id: usize) -> Self {
+                Self {
+                    inner,
+                    request_id,
+                    phantom: ::std::marker::PhantomData,
+                }
+            }
+
+            /// Retreives the index Slang returned.
+            #[inline]
+            pub fn get(&self) -> i32 {
+                self.inner
+            }
+
+            #[inline]
+            pub(crate) fn request_id(&self) -> usize {
+                self.request_id
+            }
+        }
+    };
+}
+
+/// Generate a `#[repr(i32)]` enum mirroring one in `slang.h`, along with `ALL`, `name`, `TryFrom<i32>`, `FromStr`,
+/// `Display` and, with the `serde` feature, `Serialize` and `Deserialize`.
+///
+/// Each variant is given the name `slangc` uses for it, followed by any other names `from_str` accepts.
+macro_rules! slang_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $value:expr => $string:literal $(| $alias:literal)*,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(i32)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant = $value,
+            )*
+        }
+
+        /// Autogenerated by `slang_enum`.
+        impl $name {
+            /// Every variant, in declaration order.
+            pub const ALL: &'static [$name] = &[$($name::$variant),*];
+
+            /// The name `slangc` uses, which `from_str` accepts and `Display` prints.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($name::$variant => $string,)*
+                }
+            }
+        }
+
+        /// Fails with `Error::UnknownValue` for values this crate doesn't know about.
+        impl ::std::convert::TryFrom<i32> for $name {
+            type Error = crate::Error;
+
+            fn try_from(value: i32) -> crate::Result<Self> {
+                $name::ALL
+                    .iter()
+                    .copied()
+                    .find(|&variant| variant as i32 == value)
+                    .ok_or(crate::Error::UnknownValue {
+                        kind: stringify!($name),
+                        value,
+                    })
+            }
+        }
+
+        /// Fails with `Error::UnknownName` for names `slangc` doesn't use.
+        impl ::std::str::FromStr for $name {
+            type Err = crate::Error;
+
+            fn from_str(name: &str) -> crate::Result<Self> {
+                match name {
+                    $($string $(| $alias)* => Ok($name::$variant),)*
+                    _ => Err(crate::Error::UnknownName {
+                        kind: stringify!($name),
+                        name: name.to_string(),
+                    }),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl ::serde::Serialize for $name {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.name())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> ::serde::Deserialize<'de> for $name {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+                let name = ::std::string::String::deserialize(deserializer)?;
+                name.parse().map_err(::serde::de::Error::custom)
             }
-            lifetime_wrapper_struct_impl!($wrapper_name, $inner);
         }
     };
 }
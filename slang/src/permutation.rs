@@ -0,0 +1,354 @@
+//! Generating shader variants from combinations of preprocessor defines.
+//!
+//! ```no_run
+//! use slang::batch::Batch;
+//! use slang::permutation::{DefineSet, Permutations};
+//! use slang::*;
+//!
+//! let mut base = CompileRequestBuilder::new();
+//! let spirv = base.add_target(TargetDesc::new(CompileTarget::Spirv).profile("glsl_450"));
+//! let unit = base.add_translation_unit(
+//!     TranslationUnitDesc::new(SourceLanguage::Slang, "lighting").source_string(
+//!         "lighting.slang",
+//!         "[numthreads(1, 1, 1)] void main() { /* uses USE_SHADOWS and NUM_LIGHTS */ }",
+//!     ),
+//! );
+//! let main = base.add_entry_point(unit, "main", Stage::Compute);
+//!
+//! let mut permutations = Permutations::new();
+//! permutations
+//!     .axis("USE_SHADOWS", &["0", "1"])
+//!     .axis("NUM_LIGHTS", &["1", "2", "4"])
+//!     .exclude(&[("USE_SHADOWS", "1"), ("NUM_LIGHTS", "4")]);
+//!
+//! let table = permutations.compile(&base, &Batch::new()).unwrap();
+//! let defines = DefineSet::from_pairs(&[("USE_SHADOWS", "1"), ("NUM_LIGHTS", "2")]);
+//! let code = table.get(&defines).unwrap().code(main, spirv).unwrap();
+//! ```
+
+use crate::batch::{Batch, JobResult};
+use crate::{CompileOutput, CompileRequestBuilder, Error, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// One value for each define, ordered by define name so equal sets compare and hash equal.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DefineSet(BTreeMap<String, String>);
+
+impl DefineSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_pairs(pairs: &[(&str, &str)]) -> Self {
+        pairs.iter().copied().collect()
+    }
+
+    /// Returns the previous value of `key`, if any.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.0.insert(key.into(), value.into())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over `(key, value)` pairs in order of key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// True if every pair in `other` is also in `self`.
+    pub fn contains_all(&self, other: &DefineSet) -> bool {
+        other
+            .iter()
+            .all(|(key, value)| self.get(key) == Some(value))
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> std::iter::FromIterator<(K, V)> for DefineSet {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        DefineSet(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Formatted like command line defines, e.g. `NUM_LIGHTS=2 USE_SHADOWS=1`.
+impl fmt::Display for DefineSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Axis {
+    name: String,
+    values: Vec<String>,
+}
+
+/// Define axes and exclusion rules that describe a set of shader variants.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Permutations {
+    axes: Vec<Axis>,
+    exclusions: Vec<DefineSet>,
+}
+
+impl Permutations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a define that takes each of `values` in turn. Adding an axis with no values results in no permutations.
+    pub fn axis<V: AsRef<str>>(&mut self, name: impl Into<String>, values: &[V]) -> &mut Self {
+        self.axes.push(Axis {
+            name: name.into(),
+            values: values
+                .iter()
+                .map(|value| value.as_ref().to_string())
+                .collect(),
+        });
+        self
+    }
+
+    /// Skips every combination that has all of the given define values, e.g.
+    /// `exclude(&[("USE_SHADOWS", "1"), ("NUM_LIGHTS", "4")])`.
+    pub fn exclude(&mut self, defines: &[(&str, &str)]) -> &mut Self {
+        self.exclusions.push(DefineSet::from_pairs(defines));
+        self
+    }
+
+    /// Every combination of axis values that isn't excluded, with earlier axes varying slowest.
+    pub fn combinations(&self) -> Vec<DefineSet> {
+        let mut combinations = vec![DefineSet::new()];
+        for axis in &self.axes {
+            combinations = combinations
+                .iter()
+                .flat_map(|defines| {
+                    axis.values.iter().map(move |value| {
+                        let mut defines = defines.clone();
+                        defines.insert(axis.name.clone(), value.clone());
+                        defines
+                    })
+                })
+                .collect();
+        }
+
+        combinations.retain(|defines| {
+            !self
+                .exclusions
+                .iter()
+                .any(|excluded| defines.contains_all(excluded))
+        });
+        combinations
+    }
+
+    /// Compiles `base` once per combination, with the combination's defines added to every translation unit.
+    ///
+    /// Fails with `Error::Permutation` for the first combination (in the order of `combinations`) that didn't
+    /// compile. Use `compile_each` to keep the other combinations' results.
+    pub fn compile(&self, base: &CompileRequestBuilder, batch: &Batch) -> Result<PermutationTable> {
+        let mut table = PermutationTable::default();
+        for (defines, result) in self.compile_each(base, batch) {
+            match result.output {
+                Ok(output) => table.insert(defines, output),
+                Err(error) => {
+                    return Err(Error::Permutation {
+                        defines,
+                        error: Box::new(error),
+                        diagnostics: result.diagnostics,
+                    })
+                }
+            }
+        }
+        Ok(table)
+    }
+
+    /// Like `compile`, returning every combination's result in the order of `combinations`, whether it compiled or
+    /// not.
+    pub fn compile_each(
+        &self,
+        base: &CompileRequestBuilder,
+        batch: &Batch,
+    ) -> Vec<(DefineSet, JobResult)> {
+        let combinations = self.combinations();
+        let jobs = combinations
+            .iter()
+            .map(|defines| {
+                let mut job = base.clone();
+                for (key, value) in defines.iter() {
+                    job.define(key, value);
+                }
+                job
+            })
+            .collect::<Vec<_>>();
+
+        combinations.into_iter().zip(batch.compile(&jobs)).collect()
+    }
+}
+
+/// Compiled variants keyed by their defines. Variants that compiled to identical code share one `CompileOutput`.
+#[derive(Clone, Debug, Default)]
+pub struct PermutationTable {
+    outputs: Vec<CompileOutput>,
+    variants: HashMap<DefineSet, usize>,
+    // Indices into `outputs` by a hash of their code, to find duplicates.
+    by_hash: HashMap<u64, Vec<usize>>,
+}
+
+impl PermutationTable {
+    /// Adds a compiled combination, e.g. one that `Permutations::compile_each` returned.
+    pub fn insert(&mut self, defines: DefineSet, output: CompileOutput) {
+        let mut hasher = DefaultHasher::new();
+        output.entry_points.hash(&mut hasher);
+        let outputs = &mut self.outputs;
+        let candidates = self.by_hash.entry(hasher.finish()).or_default();
+
+        // Diagnostics can mention the defines, so only the code is compared.
+        let existing = candidates
+            .iter()
+            .copied()
+            .find(|&index| outputs[index].entry_points == output.entry_points);
+        let index = match existing {
+            Some(index) => index,
+            None => {
+                outputs.push(output);
+                candidates.push(outputs.len() - 1);
+                outputs.len() - 1
+            }
+        };
+
+        self.variants.insert(defines, index);
+    }
+
+    pub fn get(&self, defines: &DefineSet) -> Option<&CompileOutput> {
+        self.variants
+            .get(defines)
+            .map(|&index| &self.outputs[index])
+    }
+
+    /// The number of define sets, including ones that share code with another.
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    /// Each distinct output once.
+    pub fn unique_outputs(&self) -> &[CompileOutput] {
+        &self.outputs
+    }
+
+    /// Iterates over every define set and its output, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&DefineSet, &CompileOutput)> {
+        self.variants
+            .iter()
+            .map(move |(defines, &index)| (defines, &self.outputs[index]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EntryPointOutput, Stage};
+
+    fn output(code: &[u8], diagnostics: &str) -> CompileOutput {
+        CompileOutput {
+            diagnostics: diagnostics.to_string(),
+            entry_points: vec![EntryPointOutput {
+                name: "main".to_string(),
+                stage: Stage::Compute,
+                code: vec![code.to_vec()],
+            }],
+            ..CompileOutput::default()
+        }
+    }
+
+    #[test]
+    fn combinations_vary_later_axes_fastest() {
+        let mut permutations = Permutations::new();
+        permutations.axis("A", &["0", "1"]).axis("B", &["x", "y"]);
+
+        assert_eq!(
+            permutations.combinations(),
+            vec![
+                DefineSet::from_pairs(&[("A", "0"), ("B", "x")]),
+                DefineSet::from_pairs(&[("A", "0"), ("B", "y")]),
+                DefineSet::from_pairs(&[("A", "1"), ("B", "x")]),
+                DefineSet::from_pairs(&[("A", "1"), ("B", "y")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn combinations_skip_exclusions() {
+        let mut permutations = Permutations::new();
+        permutations
+            .axis("A", &["0", "1"])
+            .axis("B", &["x", "y"])
+            .exclude(&[("A", "1"), ("B", "y")])
+            .exclude(&[("B", "z")]);
+
+        assert_eq!(
+            permutations.combinations(),
+            vec![
+                DefineSet::from_pairs(&[("A", "0"), ("B", "x")]),
+                DefineSet::from_pairs(&[("A", "0"), ("B", "y")]),
+                DefineSet::from_pairs(&[("A", "1"), ("B", "x")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn combinations_without_axes() {
+        assert_eq!(Permutations::new().combinations(), vec![DefineSet::new()]);
+
+        let mut permutations = Permutations::new();
+        permutations.axis("A", &["0"]).axis::<&str>("B", &[]);
+        assert!(permutations.combinations().is_empty());
+    }
+
+    #[test]
+    fn table_shares_identical_code() {
+        let a = DefineSet::from_pairs(&[("A", "0")]);
+        let b = DefineSet::from_pairs(&[("A", "1")]);
+        let c = DefineSet::from_pairs(&[("A", "2")]);
+
+        let mut table = PermutationTable::default();
+        table.insert(a.clone(), output(b"same", "warning mentions A=0"));
+        table.insert(b.clone(), output(b"same", "warning mentions A=1"));
+        table.insert(c.clone(), output(b"different", ""));
+
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.unique_outputs().len(), 2);
+        assert_eq!(table.get(&a), table.get(&b));
+        assert_eq!(table.get(&b).unwrap().diagnostics, "warning mentions A=0");
+        assert_eq!(
+            table.get(&c).unwrap().entry_points[0].code[0],
+            b"different".to_vec()
+        );
+        assert!(table.get(&DefineSet::from_pairs(&[("A", "3")])).is_none());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{Result, Session};
+use std::ops::Deref;
+use std::sync::Mutex;
+
+/// Hands out sessions to threads that want to compile in parallel.
+///
+/// Each session is used by one thread at a time, and is returned to the pool when the `PooledSession` is dropped
+/// so its builtins don't have to be registered again. The pool itself is `Sync`, so it can be shared between
+/// threads with a reference or an `Arc`.
+///
+/// ```no_run
+/// use slang::SessionPool;
+///
+/// let mut pool = SessionPool::new();
+/// pool.add_builtins("common.slang", "float square(float x) { return x * x; }");
+///
+/// std::thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| {
+///             let session = pool.get().unwrap();
+///             let request = session.create_compile_request();
+///             // ...
+///         });
+///     }
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct SessionPool {
+    builtins: Vec<(String, String)>,
+    sessions: Mutex<Vec<Session>>,
+}
+
+impl SessionPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builtins passed to `Session::add_builtins` for every session the pool creates.
+    ///
+    /// Sessions already in the pool aren't affected.
+    pub fn add_builtins(
+        &mut self,
+        source_path: impl Into<String>,
+        source_string: impl Into<String>,
+    ) -> &mut Self {
+        self.builtins
+            .push((source_path.into(), source_string.into()));
+        self
+    }
+
+    /// Takes an idle session from the pool, or creates one if there are none.
+    pub fn get(&self) -> Result<PooledSession<'_>> {
+        let idle = self.lock().pop();
+        let session = match idle {
+            Some(session) => session,
+            None => self.create_session()?,
+        };
+
+        Ok(PooledSession {
+            pool: self,
+            session: Some(session),
+        })
+    }
+
+    /// The number of idle sessions in the pool.
+    pub fn idle_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn create_session(&self) -> Result<Session> {
+        let session = Session::new();
+        for (source_path, source_string) in &self.builtins {
+            session.add_builtins(source_path, source_string)?;
+        }
+        Ok(session)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Session>> {
+        // A panic while holding the lock can't leave the list of sessions half updated.
+        self.sessions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A session checked out of a `SessionPool`, returned to it on drop.
+#[derive(Debug)]
+pub struct PooledSession<'p> {
+    pool: &'p SessionPool,
+    session: Option<Session>,
+}
+
+impl Deref for PooledSession<'_> {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        self.session.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledSession<'_> {
+    fn drop(&mut self) {
+        if let Some(session) = self.session.take() {
+            self.pool.lock().push(session);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::CompileTarget;
+use std::fmt;
+
+/// A profile Slang compiles for, e.g. `sm_5_0` or `glsl_450`.
+///
+/// Converting from a name with `From<&str>` recognizes the families below and keeps anything else as `Other`, so
+/// profiles this crate doesn't know about can still be used. Whether Slang recognizes a profile is only known once
+/// it's looked up, see `Session::find_profile` and `Session::profiles`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// A Direct3D shader model, e.g. `sm_5_0` is `Sm(5, 0)`.
+    Sm(u8, u8),
+    /// A GLSL version, e.g. `glsl_450` is `Glsl(450)`.
+    Glsl(u16),
+    /// A SPIR-V version, e.g. `spirv_1_3` is `Spirv(1, 3)`.
+    Spirv(u8, u8),
+    /// Any other profile name, e.g. a stage specific profile like `vs_5_0`.
+    Other(String),
+}
+
+impl Profile {
+    /// Every profile in the families above that some version of Slang supports, oldest first within each family.
+    pub fn known() -> Vec<Profile> {
+        let sm = [
+            (4, 0),
+            (4, 1),
+            (5, 0),
+            (5, 1),
+            (6, 0),
+            (6, 1),
+            (6, 2),
+            (6, 3),
+            (6, 4),
+            (6, 5),
+            (6, 6),
+            (6, 7),
+        ];
+        let glsl = [
+            110, 120, 130, 140, 150, 330, 400, 410, 420, 430, 440, 450, 460,
+        ];
+
+        sm.iter()
+            .map(|&(major, minor)| Profile::Sm(major, minor))
+            .chain(glsl.iter().map(|&version| Profile::Glsl(version)))
+            .chain((0..=6).map(|minor| Profile::Spirv(1, minor)))
+            .collect()
+    }
+
+    /// Whether code for `target` can be generated with this profile. DXBC needs shader model 5.1 or lower and DXIL
+    /// needs 6.0 or higher. Stage specific profiles like `vs_5_0` are treated as the shader model they name, and
+    /// other `Other` profiles are assumed to be compatible with every target.
+    pub fn is_compatible_with(&self, target: CompileTarget) -> bool {
+        match (self, target) {
+            (_, CompileTarget::TargetUnknown) | (_, CompileTarget::TargetNone) => true,
+            (Profile::Other(name), _) => match stage_shader_model(name) {
+                Some((major, minor)) => Profile::Sm(major, minor).is_compatible_with(target),
+                None => true,
+            },
+
+            (Profile::Sm(..), CompileTarget::Hlsl) => true,
+            (Profile::Sm(major, _), CompileTarget::Dxbc)
+            | (Profile::Sm(major, _), CompileTarget::DxbcAsm) => *major < 6,
+            (Profile::Sm(major, _), CompileTarget::Dxil)
+            | (Profile::Sm(major, _), CompileTarget::DxilAsm) => *major >= 6,
+
+            (Profile::Glsl(_), CompileTarget::Glsl)
+            | (Profile::Glsl(_), CompileTarget::GlslVulkan)
+            | (Profile::Glsl(_), CompileTarget::GlslVulkanOneDesc)
+            | (Profile::Glsl(_), CompileTarget::Spirv)
+            | (Profile::Glsl(_), CompileTarget::SpirvAsm) => true,
+
+            (Profile::Spirv(..), CompileTarget::Spirv)
+            | (Profile::Spirv(..), CompileTarget::SpirvAsm) => true,
+
+            _ => false,
+        }
+    }
+
+    fn parse(name: &str) -> Option<Profile> {
+        if let Some(rest) = name.strip_prefix("sm_") {
+            version(rest).map(|(major, minor)| Profile::Sm(major, minor))
+        } else if let Some(rest) = name.strip_prefix("glsl_") {
+            rest.parse().ok().map(Profile::Glsl)
+        } else if let Some(rest) = name.strip_prefix("spirv_") {
+            version(rest).map(|(major, minor)| Profile::Spirv(major, minor))
+        } else {
+            None
+        }
+    }
+}
+
+/// `major_minor`, e.g. `5_0`.
+fn version(rest: &str) -> Option<(u8, u8)> {
+    let mut parts = rest.splitn(2, '_');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// The shader model of a Direct3D stage specific profile, e.g. `(5, 0)` for `vs_5_0`.
+fn stage_shader_model(name: &str) -> Option<(u8, u8)> {
+    const STAGES: &[&str] = &["vs", "hs", "ds", "gs", "ps", "cs", "as", "ms", "lib"];
+
+    let mut parts = name.splitn(2, '_');
+    let stage = parts.next()?;
+    if !STAGES.contains(&stage) {
+        return None;
+    }
+    version(parts.next()?)
+}
+
+impl From<&str> for Profile {
+    fn from(name: &str) -> Self {
+        Profile::parse(name).unwrap_or_else(|| Profile::Other(name.to_string()))
+    }
+}
+
+impl From<String> for Profile {
+    fn from(name: String) -> Self {
+        Profile::parse(&name).unwrap_or(Profile::Other(name))
+    }
+}
+
+impl From<&String> for Profile {
+    fn from(name: &String) -> Self {
+        Profile::from(name.as_str())
+    }
+}
+
+/// The name Slang uses, e.g. `sm_5_0`.
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Profile::Sm(major, minor) => write!(f, "sm_{}_{}", major, minor),
+            Profile::Glsl(version) => write!(f, "glsl_{}", version),
+            Profile::Spirv(major, minor) => write!(f, "spirv_{}_{}", major, minor),
+            Profile::Other(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for profile in Profile::known() {
+            assert_eq!(Profile::from(profile.to_string()), profile);
+        }
+        assert_eq!(Profile::from("sm_6_5"), Profile::Sm(6, 5));
+        assert_eq!(Profile::from("glsl_450"), Profile::Glsl(450));
+        assert_eq!(Profile::from("spirv_1_3"), Profile::Spirv(1, 3));
+        assert_eq!(Profile::Sm(5, 1).to_string(), "sm_5_1");
+    }
+
+    #[test]
+    fn unknown_names_are_other() {
+        for name in &[
+            "vs_5_0", "sm_6", "sm_x_0", "glsl_", "glsl_es", "spirv_1", "", "hlsl",
+        ] {
+            let profile = Profile::from(*name);
+            assert_eq!(profile, Profile::Other(name.to_string()));
+            assert_eq!(profile.to_string(), *name);
+        }
+        assert_eq!(
+            Profile::from(&"ps_6_0".to_string()),
+            Profile::Other("ps_6_0".to_string())
+        );
+    }
+
+    #[test]
+    fn compatibility() {
+        assert!(Profile::Sm(5, 1).is_compatible_with(CompileTarget::Dxbc));
+        assert!(!Profile::Sm(5, 1).is_compatible_with(CompileTarget::Dxil));
+        assert!(Profile::Sm(6, 0).is_compatible_with(CompileTarget::Dxil));
+        assert!(!Profile::Sm(6, 0).is_compatible_with(CompileTarget::Dxbc));
+        assert!(Profile::Sm(6, 0).is_compatible_with(CompileTarget::Hlsl));
+        assert!(!Profile::Sm(6, 0).is_compatible_with(CompileTarget::Spirv));
+
+        assert!(Profile::Glsl(450).is_compatible_with(CompileTarget::Spirv));
+        assert!(Profile::Glsl(150).is_compatible_with(CompileTarget::Glsl));
+        assert!(!Profile::Glsl(450).is_compatible_with(CompileTarget::Dxil));
+        assert!(Profile::Spirv(1, 3).is_compatible_with(CompileTarget::Spirv));
+        assert!(!Profile::Spirv(1, 3).is_compatible_with(CompileTarget::Glsl));
+
+        assert!(Profile::Sm(6, 0).is_compatible_with(CompileTarget::TargetNone));
+    }
+
+    #[test]
+    fn stage_profiles_use_their_shader_model() {
+        assert!(!Profile::from("vs_5_0").is_compatible_with(CompileTarget::Dxil));
+        assert!(Profile::from("vs_5_0").is_compatible_with(CompileTarget::Dxbc));
+        assert!(Profile::from("ps_6_0").is_compatible_with(CompileTarget::Dxil));
+        assert!(!Profile::from("cs_6_0").is_compatible_with(CompileTarget::Dxbc));
+        assert!(Profile::from("lib_6_3").is_compatible_with(CompileTarget::Dxil));
+        assert!(!Profile::from("lib_6_3").is_compatible_with(CompileTarget::Spirv));
+
+        // Names that aren't stage profiles are still assumed to work.
+        assert!(Profile::from("glsl_es_300").is_compatible_with(CompileTarget::Dxil));
+        assert!(Profile::from("vs_x").is_compatible_with(CompileTarget::Dxil));
+    }
+}
@@ -0,0 +1,554 @@
+use crate::{
+    into_result, Blob, Error, ParameterCategory, Result, SlangStr, SpecializationError, Stage,
+    TypeKind,
+};
+use slang_sys::*;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+/// Slang reports unbounded sizes (e.g. unsized arrays) as `~size_t(0)`.
+pub const UNBOUNDED_SIZE: usize = !0;
+
+unsafe fn opt_cstr<'a>(ptr: *const c_char) -> Option<&'a CStr> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(ptr))
+    }
+}
+
+lifetime_wrapper_struct_copy!(ShaderReflection, *mut SlangReflection);
+lifetime_wrapper_struct_copy!(EntryPointReflection, *mut SlangReflectionEntryPoint);
+lifetime_wrapper_struct_copy!(VariableLayoutReflection, *mut SlangReflectionVariableLayout);
+lifetime_wrapper_struct_copy!(VariableReflection, *mut SlangReflectionVariable);
+lifetime_wrapper_struct_copy!(TypeLayoutReflection, *mut SlangReflectionTypeLayout);
+lifetime_wrapper_struct_copy!(TypeReflection, *mut SlangReflectionType);
+lifetime_wrapper_struct_copy!(TypeParameterReflection, *mut SlangReflectionTypeParameter);
+
+impl<'a> ShaderReflection<'a> {
+    pub fn parameter_count(&self) -> usize {
+        unsafe { spReflection_GetParameterCount(self.get()) as usize }
+    }
+
+    pub fn parameter_by_index(&self, index: usize) -> VariableLayoutReflection<'a> {
+        unsafe {
+            VariableLayoutReflection::wrap(spReflection_GetParameterByIndex(
+                self.get(),
+                index as u32,
+            ))
+        }
+    }
+
+    /// Iterates over the global shader parameters.
+    pub fn parameters(&self) -> impl Iterator<Item = VariableLayoutReflection<'a>> {
+        let this = *self;
+        (0..self.parameter_count()).map(move |i| this.parameter_by_index(i))
+    }
+
+    pub fn entry_point_count(&self) -> usize {
+        unsafe { spReflection_getEntryPointCount(self.get()) as usize }
+    }
+
+    pub fn entry_point_by_index(&self, index: usize) -> EntryPointReflection<'a> {
+        unsafe {
+            EntryPointReflection::wrap(spReflection_getEntryPointByIndex(
+                self.get(),
+                index as SlangUInt,
+            ))
+        }
+    }
+
+    pub fn entry_points(&self) -> impl Iterator<Item = EntryPointReflection<'a>> {
+        let this = *self;
+        (0..self.entry_point_count()).map(move |i| this.entry_point_by_index(i))
+    }
+
+    /// Looks up a type visible to the compiled code, e.g. to use as a type argument for specialization.
+    ///
+    /// Returns `None` for unknown types.
+    pub fn find_type_by_name(&self, name: impl SlangStr) -> Result<Option<TypeReflection<'a>>> {
+        let name = name.to_slang_str()?;
+        unsafe {
+            let ty = spReflection_FindTypeByName(self.get(), name.as_ptr());
+            if ty.is_null() {
+                Ok(None)
+            } else {
+                Ok(Some(TypeReflection::wrap(ty)))
+            }
+        }
+    }
+
+    /// Looks up every type in `names` as a type argument for specialization, failing with
+    /// `SpecializationError::UnknownType` on the first one not found.
+    ///
+    /// The arguments keep the names they were looked up with, so generic arguments like the `int` in `Foo<int>` are
+    /// passed on to Slang.
+    pub fn find_type_arguments(&self, names: &[&str]) -> Result<Vec<TypeArgument>> {
+        names
+            .iter()
+            .map(|name| {
+                if self.find_type_by_name(name)?.is_none() {
+                    return Err(Error::Specialization(SpecializationError::UnknownType(
+                        name.to_string(),
+                    )));
+                }
+                TypeArgument::named(name)
+            })
+            .collect()
+    }
+
+    /// The number of global generic type parameters (`type_param T : IFoo;`).
+    pub fn type_parameter_count(&self) -> usize {
+        unsafe { spReflection_GetTypeParameterCount(self.get()) as usize }
+    }
+
+    pub fn type_parameter_by_index(&self, index: usize) -> TypeParameterReflection<'a> {
+        unsafe {
+            TypeParameterReflection::wrap(spReflection_GetTypeParameterByIndex(
+                self.get(),
+                index as u32,
+            ))
+        }
+    }
+
+    pub fn type_parameters(&self) -> impl Iterator<Item = TypeParameterReflection<'a>> {
+        let this = *self;
+        (0..self.type_parameter_count()).map(move |i| this.type_parameter_by_index(i))
+    }
+
+    /// Returns `None` for unknown type parameters.
+    pub fn find_type_parameter(
+        &self,
+        name: impl SlangStr,
+    ) -> Result<Option<TypeParameterReflection<'a>>> {
+        let name = name.to_slang_str()?;
+        unsafe {
+            let type_parameter = spReflection_FindTypeParameter(self.get(), name.as_ptr());
+            if type_parameter.is_null() {
+                Ok(None)
+            } else {
+                Ok(Some(TypeParameterReflection::wrap(type_parameter)))
+            }
+        }
+    }
+
+    /// Global parameters that occupy the push constant category.
+    pub fn push_constants(&self) -> Vec<PushConstantReflection<'a>> {
+        self.parameters()
+            .filter_map(PushConstantReflection::new)
+            .collect()
+    }
+
+    /// Combines the push constants of every entry point (and the global push constants, which are visible to all of
+    /// them) into a single range, as used when creating a Vulkan pipeline layout.
+    ///
+    /// Returns `None` if nothing occupies the push constant category.
+    pub fn push_constant_range(&self) -> Option<PushConstantRange> {
+        let stages = self
+            .entry_points()
+            .filter_map(|entry_point| entry_point.stage())
+            .collect::<Vec<_>>();
+
+        let mut range: Option<PushConstantRange> = None;
+
+        for push_constant in self.push_constants() {
+            PushConstantRange::include(
+                &mut range,
+                push_constant.offset,
+                push_constant.size,
+                &stages,
+            );
+        }
+
+        for entry_point in self.entry_points() {
+            let stage = entry_point.stage().into_iter().collect::<Vec<_>>();
+            for push_constant in entry_point.push_constants() {
+                PushConstantRange::include(
+                    &mut range,
+                    push_constant.offset,
+                    push_constant.size,
+                    &stage,
+                );
+            }
+        }
+
+        range
+    }
+}
+
+impl<'a> EntryPointReflection<'a> {
+    pub fn name(&self) -> &'a CStr {
+        unsafe { CStr::from_ptr(spReflectionEntryPoint_getName(self.get())) }
+    }
+
+    pub fn stage(&self) -> Option<Stage> {
+        unsafe { Stage::try_from(spReflectionEntryPoint_getStage(self.get()) as i32).ok() }
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        unsafe { spReflectionEntryPoint_getParameterCount(self.get()) as usize }
+    }
+
+    pub fn parameter_by_index(&self, index: usize) -> VariableLayoutReflection<'a> {
+        unsafe {
+            VariableLayoutReflection::wrap(spReflectionEntryPoint_getParameterByIndex(
+                self.get(),
+                index as u32,
+            ))
+        }
+    }
+
+    /// Iterates over the entry point's (uniform and varying) parameters.
+    pub fn parameters(&self) -> impl Iterator<Item = VariableLayoutReflection<'a>> {
+        let this = *self;
+        (0..self.parameter_count()).map(move |i| this.parameter_by_index(i))
+    }
+
+    /// Entry point parameters that occupy the push constant category.
+    pub fn push_constants(&self) -> Vec<PushConstantReflection<'a>> {
+        self.parameters()
+            .filter_map(PushConstantReflection::new)
+            .collect()
+    }
+}
+
+impl<'a> VariableLayoutReflection<'a> {
+    pub fn variable(&self) -> VariableReflection<'a> {
+        unsafe { VariableReflection::wrap(spReflectionVariableLayout_GetVariable(self.get())) }
+    }
+
+    pub fn name(&self) -> Option<&'a CStr> {
+        self.variable().name()
+    }
+
+    pub fn type_layout(&self) -> TypeLayoutReflection<'a> {
+        unsafe { TypeLayoutReflection::wrap(spReflectionVariableLayout_GetTypeLayout(self.get())) }
+    }
+
+    /// The offset of this variable in `category` units, e.g. the register index or the byte offset for `Uniform`.
+    pub fn offset(&self, category: ParameterCategory) -> usize {
+        unsafe {
+            spReflectionVariableLayout_GetOffset(self.get(), category as SlangParameterCategory)
+        }
+    }
+
+    /// The register space (or descriptor set) of this variable for `category`.
+    pub fn binding_space(&self, category: ParameterCategory) -> usize {
+        unsafe {
+            spReflectionVariableLayout_GetSpace(self.get(), category as SlangParameterCategory)
+        }
+    }
+
+    /// The binding index for the variable's primary category.
+    pub fn binding_index(&self) -> u32 {
+        unsafe { spReflectionParameter_GetBindingIndex(self.get()) }
+    }
+
+    pub fn category(&self) -> Option<ParameterCategory> {
+        self.type_layout().parameter_category()
+    }
+
+    /// Whether this variable occupies the push constant category, e.g. a `[[vk::push_constant]]` constant buffer.
+    pub fn is_push_constant(&self) -> bool {
+        self.type_layout()
+            .categories()
+            .any(|category| category == ParameterCategory::PushConstantBuffer)
+    }
+}
+
+impl<'a> VariableReflection<'a> {
+    pub fn name(&self) -> Option<&'a CStr> {
+        unsafe { opt_cstr(spReflectionVariable_GetName(self.get())) }
+    }
+
+    pub fn ty(&self) -> TypeReflection<'a> {
+        unsafe { TypeReflection::wrap(spReflectionVariable_GetType(self.get())) }
+    }
+}
+
+impl<'a> TypeLayoutReflection<'a> {
+    pub fn ty(&self) -> TypeReflection<'a> {
+        unsafe { TypeReflection::wrap(spReflectionTypeLayout_GetType(self.get())) }
+    }
+
+    pub fn kind(&self) -> Option<TypeKind> {
+        self.ty().kind()
+    }
+
+    /// The size of this type in `category` units. May be `UNBOUNDED_SIZE`.
+    pub fn size(&self, category: ParameterCategory) -> usize {
+        unsafe { spReflectionTypeLayout_GetSize(self.get(), category as SlangParameterCategory) }
+    }
+
+    /// The single category this type consumes, `Some(ParameterCategory::Mixed)` if it consumes several.
+    pub fn parameter_category(&self) -> Option<ParameterCategory> {
+        unsafe {
+            ParameterCategory::try_from(
+                spReflectionTypeLayout_GetParameterCategory(self.get()) as i32
+            )
+            .ok()
+        }
+    }
+
+    pub fn category_count(&self) -> usize {
+        unsafe { spReflectionTypeLayout_GetCategoryCount(self.get()) as usize }
+    }
+
+    pub fn category_by_index(&self, index: usize) -> Option<ParameterCategory> {
+        unsafe {
+            ParameterCategory::try_from(spReflectionTypeLayout_GetCategoryByIndex(
+                self.get(),
+                index as u32,
+            ) as i32)
+            .ok()
+        }
+    }
+
+    /// Iterates over every category this type consumes, skipping ones this crate doesn't know about.
+    pub fn categories(&self) -> impl Iterator<Item = ParameterCategory> + 'a {
+        let this = *self;
+        (0..self.category_count()).filter_map(move |i| this.category_by_index(i))
+    }
+
+    pub fn field_count(&self) -> usize {
+        self.ty().field_count()
+    }
+
+    pub fn field_by_index(&self, index: usize) -> VariableLayoutReflection<'a> {
+        unsafe {
+            VariableLayoutReflection::wrap(spReflectionTypeLayout_GetFieldByIndex(
+                self.get(),
+                index as u32,
+            ))
+        }
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = VariableLayoutReflection<'a>> {
+        let this = *self;
+        (0..self.field_count()).map(move |i| this.field_by_index(i))
+    }
+
+    /// The layout of the element type for arrays, constant buffers and parameter blocks.
+    pub fn element_type_layout(&self) -> Option<TypeLayoutReflection<'a>> {
+        unsafe {
+            let element = spReflectionTypeLayout_GetElementTypeLayout(self.get());
+            if element.is_null() {
+                None
+            } else {
+                Some(TypeLayoutReflection::wrap(element))
+            }
+        }
+    }
+
+    /// The layout of the element variable for constant buffers and parameter blocks.
+    pub fn element_var_layout(&self) -> Option<VariableLayoutReflection<'a>> {
+        unsafe {
+            let element = spReflectionTypeLayout_GetElementVarLayout(self.get());
+            if element.is_null() {
+                None
+            } else {
+                Some(VariableLayoutReflection::wrap(element))
+            }
+        }
+    }
+}
+
+impl<'a> TypeReflection<'a> {
+    pub fn kind(&self) -> Option<TypeKind> {
+        unsafe { TypeKind::try_from(spReflectionType_GetKind(self.get()) as i32).ok() }
+    }
+
+    pub fn name(&self) -> Option<&'a CStr> {
+        unsafe { opt_cstr(spReflectionType_GetName(self.get())) }
+    }
+
+    /// The name including generic arguments, e.g. `Foo<int>` where `name` is just `Foo`.
+    pub fn full_name(&self) -> Result<CString> {
+        unsafe {
+            let mut blob = ptr::null_mut();
+            into_result(spReflectionType_GetFullName(self.get(), &mut blob))?;
+            let blob = Blob::from_raw(blob);
+            // The blob may or may not include the terminating NUL.
+            let bytes = blob.as_ref();
+            let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+            CString::new(bytes).map_err(Error::InteriorNul)
+        }
+    }
+
+    pub fn field_count(&self) -> usize {
+        unsafe { spReflectionType_GetFieldCount(self.get()) as usize }
+    }
+}
+
+impl<'a> TypeParameterReflection<'a> {
+    pub fn name(&self) -> &'a CStr {
+        unsafe { CStr::from_ptr(spReflectionTypeParameter_GetName(self.get())) }
+    }
+
+    pub fn index(&self) -> usize {
+        unsafe { spReflectionTypeParameter_GetIndex(self.get()) as usize }
+    }
+
+    pub fn constraint_count(&self) -> usize {
+        unsafe { spReflectionTypeParameter_GetConstraintCount(self.get()) as usize }
+    }
+
+    pub fn constraint_by_index(&self, index: usize) -> TypeReflection<'a> {
+        unsafe {
+            TypeReflection::wrap(spReflectionTypeParameter_GetConstraintByIndex(
+                self.get(),
+                index as u32,
+            ))
+        }
+    }
+
+    /// The interfaces a type argument for this parameter has to conform to.
+    pub fn constraints(&self) -> impl Iterator<Item = TypeReflection<'a>> {
+        let this = *self;
+        (0..self.constraint_count()).map(move |i| this.constraint_by_index(i))
+    }
+}
+
+/// A type argument for specializing a generic entry point or the global generic parameters.
+///
+/// Slang specializes by type name, so this holds the type's full name, e.g. `Foo<int>`, rather than a reflected
+/// type, whose `name` leaves out generic arguments.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TypeArgument {
+    name: CString,
+}
+
+impl TypeArgument {
+    /// A type argument that Slang looks up when the request is compiled. Use
+    /// `ShaderReflection::find_type_arguments` to check that the types exist first.
+    pub fn named(name: impl SlangStr) -> Result<Self> {
+        Ok(Self {
+            name: name.to_slang_str()?.into_owned(),
+        })
+    }
+
+    /// A type argument for a reflected type, e.g. one of the `constraints` of a type parameter.
+    pub fn from_type(ty: &TypeReflection) -> Result<Self> {
+        Ok(Self {
+            name: ty.full_name()?,
+        })
+    }
+
+    pub fn name(&self) -> &CStr {
+        &self.name
+    }
+}
+
+impl SlangStr for TypeArgument {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        Ok(Cow::Borrowed(&self.name))
+    }
+}
+
+/// A parameter that occupies the push constant category.
+#[derive(Copy, Clone, Debug)]
+pub struct PushConstantReflection<'a> {
+    pub parameter: VariableLayoutReflection<'a>,
+    /// The layout of the push constant data, i.e. the element type of a `ConstantBuffer<T>`.
+    pub layout: TypeLayoutReflection<'a>,
+    /// The offset of the push constant data in bytes.
+    pub offset: usize,
+    /// The size of the push constant data in bytes.
+    pub size: usize,
+}
+
+impl<'a> PushConstantReflection<'a> {
+    fn new(parameter: VariableLayoutReflection<'a>) -> Option<Self> {
+        if !parameter.is_push_constant() {
+            return None;
+        }
+
+        let type_layout = parameter.type_layout();
+        let layout = type_layout.element_type_layout().unwrap_or(type_layout);
+
+        Some(PushConstantReflection {
+            parameter,
+            layout,
+            offset: parameter.offset(ParameterCategory::Uniform),
+            size: layout.size(ParameterCategory::Uniform),
+        })
+    }
+
+    pub fn name(&self) -> Option<&'a CStr> {
+        self.parameter.name()
+    }
+}
+
+/// A push constant range for a pipeline, mirroring `VkPushConstantRange`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PushConstantRange {
+    /// The stages of the entry points that can access the range.
+    pub stages: Vec<Stage>,
+    /// The offset of the range in bytes.
+    pub offset: usize,
+    /// The size of the range in bytes.
+    pub size: usize,
+}
+
+impl PushConstantRange {
+    /// Grows `range` to cover `size` bytes at `offset` and to be accessible from `stages`.
+    fn include(range: &mut Option<Self>, offset: usize, size: usize, stages: &[Stage]) {
+        let range = range.get_or_insert_with(|| PushConstantRange {
+            stages: Vec::new(),
+            offset,
+            size: 0,
+        });
+
+        let end = (range.offset + range.size).max(offset + size);
+        range.offset = range.offset.min(offset);
+        range.size = end - range.offset;
+
+        for &stage in stages {
+            if !range.stages.contains(&stage) {
+                range.stages.push(stage);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_constant_range_covers_every_push_constant() {
+        let mut range = None;
+        PushConstantRange::include(&mut range, 16, 16, &[Stage::Vertex]);
+        assert_eq!(
+            range,
+            Some(PushConstantRange {
+                stages: vec![Stage::Vertex],
+                offset: 16,
+                size: 16,
+            })
+        );
+
+        // Overlapping, and extending the range on both sides.
+        PushConstantRange::include(&mut range, 8, 12, &[Stage::Fragment]);
+        PushConstantRange::include(&mut range, 24, 40, &[Stage::Vertex, Stage::Fragment]);
+        assert_eq!(
+            range,
+            Some(PushConstantRange {
+                stages: vec![Stage::Vertex, Stage::Fragment],
+                offset: 8,
+                size: 56,
+            })
+        );
+
+        // Contained in the range already.
+        PushConstantRange::include(&mut range, 12, 4, &[Stage::Compute]);
+        assert_eq!(
+            range.as_ref().map(|range| (range.offset, range.size)),
+            Some((8, 56))
+        );
+        assert_eq!(range.unwrap().stages.len(), 3);
+    }
+}
@@ -1,100 +1,535 @@
 #![allow(overflowing_literals)]
 
-/// SLANG_OK indicates success, and is equivalent to SLANG_MAKE_SUCCESS(SLANG_FACILITY_WIN_GENERAL, 0)
-const S_OK: slang_sys::SlangResult = 0x00000000;
-/// SLANG_FAIL is the generic failure code - meaning a serious error occurred and the call couldn't complete
-const E_FAIL: slang_sys::SlangResult = 0x80004005;
-
-/// Functionality is not implemented
-const E_NOT_IMPLEMENTED: slang_sys::SlangResult = 0x80004001;
-/// Interface not be found
-const E_NO_INTERFACE: slang_sys::SlangResult = 0x80004002;
-/// Operation was aborted (did not correctly complete)
-const E_ABORT: slang_sys::SlangResult = 0x80004004;
-
-/// Indicates that a handle passed in as parameter to a method is invalid.
-const E_INVALID_HANDLE: slang_sys::SlangResult = 0x80070006;
-/// Indicates that an argument passed in as parameter to a method is invalid.
-const E_INVALID_ARG: slang_sys::SlangResult = 0x80070057;
-/// Operation could not complete - ran out of memory
-const E_OUT_OF_MEMORY: slang_sys::SlangResult = 0x8007000e;
-
-// Supplied buffer is too small to be able to complete
-const SLANG_E_BUFFER_TOO_SMALL: slang_sys::SlangResult = 0x82000001;
-/// Used to identify a Result that has yet to be initialized.
-/// It defaults to failure such that if used incorrectly will fail, as similar in concept to using an uninitialized variable.
-const SLANG_E_UNINITIALIZED: slang_sys::SlangResult = 0x82000002;
-/// Returned from an async method meaning the output is invalid (thus an error), but a result for the request is pending, and will be returned on a subsequent call with the async handle.
-const SLANG_E_PENDING: slang_sys::SlangResult = 0x82000003;
-/// Indicates a file/resource could not be opened
-const SLANG_E_CANNOT_OPEN: slang_sys::SlangResult = 0x82000004;
-/// Indicates a file/resource could not be found
-const SLANG_E_NOT_FOUND: slang_sys::SlangResult = 0x82000005;
-/// An unhandled internal failure (typically from unhandled exception)
-const SLANG_E_INTERNAL_FAIL: slang_sys::SlangResult = 0x82000006;
-
-// TODO impl Display for SlangResult wrapper? can parse severity, facility, code
-pub type Result<T> = ::std::result::Result<T, Error>;
+use crate::{CompileTarget, Diagnostic, DiagnosticSeverity, Profile};
 
-// TODO: unsure how to get a From<SlangResult> impl since I own neither SlangResult (i32) or Result
-pub fn into_result(slang_result: slang_sys::SlangResult) -> Result<()> {
-    if slang_result == 0 {
-        Ok(())
-    } else {
-        Err(match slang_result {
-            x if x == E_FAIL => Error::Fail,
-            x if x == E_NOT_IMPLEMENTED => Error::NotImplemented,
-            x if x == E_NO_INTERFACE => Error::NoInterface,
-            x if x == E_ABORT => Error::Abort,
-            x if x == E_INVALID_HANDLE => Error::InvalidHandle,
-            x if x == E_INVALID_ARG => Error::InvalidArg,
-            x if x == E_OUT_OF_MEMORY => Error::OutOfMemory,
-            x if x == SLANG_E_BUFFER_TOO_SMALL => Error::SlangErrorBufferTooSmall,
-            x if x == SLANG_E_UNINITIALIZED => Error::SlangErrorUninitialized,
-            x if x == SLANG_E_PENDING => Error::SlangErrorPending,
-            x if x == SLANG_E_CANNOT_OPEN => Error::SlangErrorCannotOpen,
-            x if x == SLANG_E_NOT_FOUND => Error::SlangErrorNotFound,
-            x if x == SLANG_E_INTERNAL_FAIL => Error::SlangErrorInternalFail,
-            result => Error::Unknown(result),
+/// An HRESULT-style status code returned by Slang. The top bit is the severity (set for failures), the next 15
+/// bits are the facility that produced it and the low 16 bits are the code within that facility.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SlangResult(pub slang_sys::SlangResult);
+
+impl SlangResult {
+    /// SLANG_OK indicates success, and is equivalent to SLANG_MAKE_SUCCESS(SLANG_FACILITY_WIN_GENERAL, 0)
+    pub const OK: Self = SlangResult(0x00000000);
+    /// SLANG_FAIL is the generic failure code - meaning a serious error occurred and the call couldn't complete
+    pub const FAIL: Self = SlangResult(0x80004005);
+
+    /// Functionality is not implemented
+    pub const NOT_IMPLEMENTED: Self = SlangResult(0x80004001);
+    /// Interface not be found
+    pub const NO_INTERFACE: Self = SlangResult(0x80004002);
+    /// Operation was aborted (did not correctly complete)
+    pub const ABORT: Self = SlangResult(0x80004004);
+
+    /// Indicates that a handle passed in as parameter to a method is invalid.
+    pub const INVALID_HANDLE: Self = SlangResult(0x80070006);
+    /// Indicates that an argument passed in as parameter to a method is invalid.
+    pub const INVALID_ARG: Self = SlangResult(0x80070057);
+    /// Operation could not complete - ran out of memory
+    pub const OUT_OF_MEMORY: Self = SlangResult(0x8007000e);
+
+    /// Supplied buffer is too small to be able to complete
+    pub const BUFFER_TOO_SMALL: Self = SlangResult(0x82000001);
+    /// Used to identify a Result that has yet to be initialized.
+    /// It defaults to failure such that if used incorrectly will fail, as similar in concept to using an uninitialized variable.
+    pub const UNINITIALIZED: Self = SlangResult(0x82000002);
+    /// Returned from an async method meaning the output is invalid (thus an error), but a result for the request is pending, and will be returned on a subsequent call with the async handle.
+    pub const PENDING: Self = SlangResult(0x82000003);
+    /// Indicates a file/resource could not be opened
+    pub const CANNOT_OPEN: Self = SlangResult(0x82000004);
+    /// Indicates a file/resource could not be found
+    pub const NOT_FOUND: Self = SlangResult(0x82000005);
+    /// An unhandled internal failure (typically from unhandled exception)
+    pub const INTERNAL_FAIL: Self = SlangResult(0x82000006);
+
+    pub const SEVERITY_SUCCESS: i32 = 0;
+    pub const SEVERITY_ERROR: i32 = 1;
+
+    pub const FACILITY_WIN_GENERAL: i32 = 0;
+    pub const FACILITY_WIN_INTERFACE: i32 = 4;
+    pub const FACILITY_WIN_API: i32 = 7;
+    /// Slang's own facilities start here.
+    pub const FACILITY_BASE: i32 = 0x200;
+    /// The same as `FACILITY_BASE`, used by results like `NOT_FOUND`.
+    pub const FACILITY_CORE: i32 = Self::FACILITY_BASE;
+    pub const FACILITY_INTERNAL: i32 = Self::FACILITY_BASE + 1;
+    /// Facilities for code outside of Slang start here.
+    pub const FACILITY_EXTERNAL_BASE: i32 = 0x210;
+
+    const NAMES: &'static [(SlangResult, &'static str)] = &[
+        (Self::OK, "SLANG_OK"),
+        (Self::FAIL, "E_FAIL"),
+        (Self::NOT_IMPLEMENTED, "E_NOT_IMPLEMENTED"),
+        (Self::NO_INTERFACE, "E_NO_INTERFACE"),
+        (Self::ABORT, "E_ABORT"),
+        (Self::INVALID_HANDLE, "E_INVALID_HANDLE"),
+        (Self::INVALID_ARG, "E_INVALID_ARG"),
+        (Self::OUT_OF_MEMORY, "E_OUT_OF_MEMORY"),
+        (Self::BUFFER_TOO_SMALL, "SLANG_E_BUFFER_TOO_SMALL"),
+        (Self::UNINITIALIZED, "SLANG_E_UNINITIALIZED"),
+        (Self::PENDING, "SLANG_E_PENDING"),
+        (Self::CANNOT_OPEN, "SLANG_E_CANNOT_OPEN"),
+        (Self::NOT_FOUND, "SLANG_E_NOT_FOUND"),
+        (Self::INTERNAL_FAIL, "SLANG_E_INTERNAL_FAIL"),
+    ];
+
+    /// True for success codes, which includes every result with the severity bit clear, not just `OK`.
+    pub fn is_ok(self) -> bool {
+        self.0 >= 0
+    }
+
+    pub fn is_err(self) -> bool {
+        !self.is_ok()
+    }
+
+    /// `SEVERITY_SUCCESS` or `SEVERITY_ERROR`.
+    pub fn severity(self) -> i32 {
+        (self.0 >> 31) & 1
+    }
+
+    /// One of the `FACILITY_*` constants, or a facility defined outside of Slang.
+    pub fn facility(self) -> i32 {
+        (self.0 >> 16) & 0x7fff
+    }
+
+    pub fn code(self) -> i32 {
+        self.0 & 0xffff
+    }
+
+    /// The name Slang gives this result, e.g. `"E_FAIL"`, if it's one of the predefined results.
+    pub fn name(self) -> Option<&'static str> {
+        Self::NAMES
+            .iter()
+            .find(|(result, _)| *result == self)
+            .map(|(_, name)| *name)
+    }
+
+    /// `Ok` for success codes, otherwise `Error::Slang`.
+    pub fn into_result(self) -> Result<()> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(Error::Slang(self))
+        }
+    }
+
+    fn facility_name(facility: i32) -> Option<&'static str> {
+        Some(match facility {
+            Self::FACILITY_WIN_GENERAL => "SLANG_FACILITY_WIN_GENERAL",
+            Self::FACILITY_WIN_INTERFACE => "SLANG_FACILITY_WIN_INTERFACE",
+            Self::FACILITY_WIN_API => "SLANG_FACILITY_WIN_API",
+            Self::FACILITY_CORE => "SLANG_FACILITY_CORE",
+            Self::FACILITY_INTERNAL => "SLANG_FACILITY_INTERNAL",
+            Self::FACILITY_EXTERNAL_BASE => "SLANG_FACILITY_EXTERNAL_BASE",
+            _ => return None,
         })
     }
 }
 
+impl From<slang_sys::SlangResult> for SlangResult {
+    fn from(result: slang_sys::SlangResult) -> Self {
+        SlangResult(result)
+    }
+}
+
+impl std::fmt::Debug for SlangResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "SlangResult({:#010x})", self.0)
+    }
+}
+
+/// The result's name, e.g. `E_FAIL`, or `facility:code` for results without one, e.g.
+/// `SLANG_FACILITY_CORE:0x0012` or `0x0300:0x0001`.
+impl std::fmt::Display for SlangResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(name) = self.name() {
+            return write!(f, "{}", name);
+        }
+        match Self::facility_name(self.facility()) {
+            Some(facility) => write!(f, "{}:{:#06x}", facility, self.code()),
+            None => write!(f, "{:#06x}:{:#06x}", self.facility(), self.code()),
+        }
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+pub fn into_result(slang_result: slang_sys::SlangResult) -> Result<()> {
+    SlangResult(slang_result).into_result()
+}
+
 #[derive(Debug)]
 pub enum Error {
-    Fail,
-    NotImplemented,
-    NoInterface,
-    Abort,
-    InvalidHandle,
-    InvalidArg,
-    OutOfMemory,
-    SlangErrorBufferTooSmall,
-    SlangErrorUninitialized,
-    SlangErrorPending,
-    SlangErrorCannotOpen,
-    SlangErrorNotFound,
-    SlangErrorInternalFail,
-    Unknown(slang_sys::SlangResult),
+    /// A Slang call failed with this result.
+    Slang(SlangResult),
+    /// Compiling a request failed, usually because of errors in the shader.
+    Compile(CompileError),
+    Specialization(SpecializationError),
+    Builder(BuilderError),
+    /// Compiling one combination of `Permutations` failed.
+    Permutation {
+        defines: crate::permutation::DefineSet,
+        error: Box<Error>,
+        /// Everything Slang reported for the combination, see `JobResult::diagnostics`.
+        diagnostics: String,
+    },
+    /// `CompileRequest::process_command_line_arguments` rejected an argument.
+    CommandLine {
+        error: Box<Error>,
+        /// Slang's explanation of which argument was invalid.
+        diagnostics: String,
+    },
+    /// Reading or writing a file failed.
+    Io(std::io::Error),
+    /// A string passed to Slang contains a NUL byte.
+    InteriorNul(std::ffi::NulError),
+    /// A path passed to Slang can't be converted to UTF-8.
+    NonUnicodePath(std::path::PathBuf),
+    /// A raw value doesn't match any variant of the enum named `kind`, e.g. from a newer version of Slang.
+    UnknownValue {
+        kind: &'static str,
+        value: i32,
+    },
+    /// A target, translation unit or entry point index that wasn't returned by the request it's used with, or a
+    /// target index that `CompileRequest::set_code_gen_target` replaced.
+    UnknownIndex {
+        kind: &'static str,
+        index: i32,
+    },
+    /// A name doesn't match any variant of the enum named `kind`.
+    UnknownName {
+        kind: &'static str,
+        name: String,
+    },
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Error::Fail => write!(f, "E_FAIL"),
-            Error::NotImplemented => write!(f, "E_NOT_IMPLEMENTED"),
-            Error::NoInterface => write!(f, "E_NO_INTERFACE"),
-            Error::Abort => write!(f, "E_ABORT"),
-            Error::InvalidHandle => write!(f, "E_INVALID_HANDLE"),
-            Error::InvalidArg => write!(f, "E_INVALID_ARG"),
-            Error::OutOfMemory => write!(f, "E_OUT_OF_MEMORY"),
-            Error::SlangErrorBufferTooSmall => write!(f, "SLANG_E_BUFFER_TOO_SMALL"),
-            Error::SlangErrorUninitialized => write!(f, "SLANG_E_UNINITIALIZED"),
-            Error::SlangErrorPending => write!(f, "SLANG_E_PENDING"),
-            Error::SlangErrorCannotOpen => write!(f, "SLANG_E_CANNOT_OPEN"),
-            Error::SlangErrorNotFound => write!(f, "SLANG_E_NOT_FOUND"),
-            Error::SlangErrorInternalFail => write!(f, "SLANG_E_INTERNAL_FAIL"),
-            Error::Unknown(result) => write!(f, "Unknown HResult: {}", result),
+            Error::Slang(result) => write!(f, "{}", result),
+            Error::Compile(error) => write!(f, "{}", error),
+            Error::Specialization(error) => write!(f, "{}", error),
+            Error::Builder(error) => write!(f, "{}", error),
+            Error::Permutation { defines, .. } => {
+                write!(f, "permutation '{}' failed to compile", defines)
+            }
+            Error::CommandLine { diagnostics, .. } => {
+                write!(f, "invalid command line arguments")?;
+                if !diagnostics.trim().is_empty() {
+                    write!(f, ": {}", diagnostics.trim_end())?;
+                }
+                Ok(())
+            }
+            Error::Io(error) => write!(f, "{}", error),
+            Error::InteriorNul(error) => write!(f, "{}", error),
+            Error::NonUnicodePath(path) => write!(f, "path is not valid UTF-8: {:?}", path),
+            Error::UnknownValue { kind, value } => write!(f, "unknown {} value {}", kind, value),
+            Error::UnknownIndex { kind, index } => {
+                write!(f, "{} index {} isn't from this request", kind, index)
+            }
+            Error::UnknownName { kind, name } => write!(f, "unknown {} '{}'", kind, name),
+        }
+    }
+}
+
+/// Variants that wrap another error display it as their own message, so their source is the wrapped error's
+/// source. `Permutation` and `CommandLine` only describe what failed, with the error itself as the source.
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Specialization(error) => error.source(),
+            Error::Builder(error) => error.source(),
+            Error::Compile(error) => error.source(),
+            Error::Permutation { error, .. } | Error::CommandLine { error, .. } => Some(&**error),
+            Error::Io(error) => error.source(),
+            Error::InteriorNul(error) => error.source(),
+            Error::Slang(_)
+            | Error::NonUnicodePath(_)
+            | Error::UnknownValue { .. }
+            | Error::UnknownIndex { .. }
+            | Error::UnknownName { .. } => None,
+        }
+    }
+}
+
+impl From<SlangResult> for Error {
+    fn from(result: SlangResult) -> Self {
+        Error::Slang(result)
+    }
+}
+
+/// Returned by `CompileRequest::compile` when Slang fails to compile, with the diagnostics explaining why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    /// What `spCompile` returned, usually `SlangResult::FAIL`.
+    pub result: SlangResult,
+    /// Every diagnostic that could be parsed from `output`, including warnings and notes.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Slang's diagnostic output as is.
+    pub output: String,
+    /// The intermediate files written before the compile failed, when compiled with
+    /// `CompileRequestBuilder::dump_intermediates`. Always empty from `CompileRequest::compile`.
+    pub intermediates: Vec<(String, Vec<u8>)>,
+}
+
+impl CompileError {
+    pub(crate) fn new(result: SlangResult, output: String) -> Self {
+        Self {
+            result,
+            diagnostics: Diagnostic::parse_all(&output),
+            output,
+            intermediates: Vec::new(),
+        }
+    }
+
+    /// The diagnostics that are errors, rather than warnings or notes.
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity >= DiagnosticSeverity::Error)
+    }
+}
+
+/// Slang's diagnostic output, or just the result if there was none.
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let output = self.output.trim_end();
+        if output.is_empty() {
+            write!(f, "compilation failed ({})", self.result)
+        } else {
+            write!(f, "{}", output)
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// Errors from validating a `CompileRequestBuilder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuilderError {
+    /// No targets were added.
+    NoTargets,
+    /// An entry point refers to a translation unit id that's out of range for the builder.
+    UnknownTranslationUnit { entry_point: String },
+    /// Slang doesn't recognize the profile name.
+    UnknownProfile(String),
+    /// The profile is for a different kind of target, e.g. `glsl_450` for DXIL.
+    IncompatibleProfile {
+        profile: Profile,
+        target: CompileTarget,
+    },
+}
+
+impl std::fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BuilderError::NoTargets => write!(f, "no targets were added"),
+            BuilderError::UnknownTranslationUnit { entry_point } => write!(
+                f,
+                "entry point '{}' refers to an unknown translation unit",
+                entry_point
+            ),
+            BuilderError::UnknownProfile(name) => write!(f, "unknown profile '{}'", name),
+            BuilderError::IncompatibleProfile { profile, target } => write!(
+                f,
+                "profile '{}' can't be used with target {:?}",
+                profile, target
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
+/// Errors from specializing generic entry points and global generic parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecializationError {
+    /// No type with this name could be found through reflection.
+    UnknownType(String),
+    /// A type argument doesn't conform to the interface its generic parameter is constrained to.
+    ///
+    /// The names are read from the quoted names in Slang's diagnostic. When they can't be, `compile` fails with
+    /// `Error::Compile` instead.
+    ConstraintNotSatisfied {
+        argument: String,
+        interface: String,
+        /// The diagnostic Slang reported.
+        message: String,
+    },
+}
+
+impl SpecializationError {
+    /// Slang's `typeArgumentDoesNotConformToInterface` diagnostic.
+    const DOES_NOT_CONFORM_CODE: u32 = 38029;
+
+    /// Finds the first "type argument does not conform" error in Slang's diagnostics.
+    ///
+    /// Slang doesn't report the types separately, so the argument and interface names are the first two quoted
+    /// names in the diagnostic's message. Returns `None` if the message doesn't have them, e.g. because a newer
+    /// version of Slang words it differently, so that the caller can fall back to the full diagnostics.
+    pub(crate) fn from_diagnostics(diagnostics: &[Diagnostic]) -> Option<Self> {
+        let diagnostic = diagnostics.iter().find(|diagnostic| {
+            diagnostic.severity >= DiagnosticSeverity::Error
+                && diagnostic.code == Some(Self::DOES_NOT_CONFORM_CODE)
+        })?;
+
+        // e.g. "type argument 'Foo' does not conform to the required interface 'IBar'"
+        let parts = diagnostic.message.split('\'').collect::<Vec<_>>();
+        // Two names need four quotes, which split the message into at least five parts.
+        if parts.len() < 5 {
+            return None;
+        }
+        let argument = parts[1].to_string();
+        let interface = parts[3].to_string();
+
+        Some(SpecializationError::ConstraintNotSatisfied {
+            argument,
+            interface,
+            message: diagnostic.message.clone(),
+        })
+    }
+}
+
+impl std::fmt::Display for SpecializationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SpecializationError::UnknownType(name) => write!(f, "unknown type '{}'", name),
+            SpecializationError::ConstraintNotSatisfied {
+                argument,
+                interface,
+                ..
+            } => write!(
+                f,
+                "type argument '{}' does not conform to interface '{}'",
+                argument, interface
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SpecializationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permutation::DefineSet;
+    use std::error::Error as _;
+
+    #[test]
+    fn slang_result_fields() {
+        assert!(SlangResult::OK.is_ok());
+        assert_eq!(SlangResult::OK.severity(), SlangResult::SEVERITY_SUCCESS);
+        assert_eq!(
+            SlangResult::OK.facility(),
+            SlangResult::FACILITY_WIN_GENERAL
+        );
+        assert_eq!(SlangResult::OK.code(), 0);
+
+        assert!(SlangResult::FAIL.is_err());
+        assert_eq!(SlangResult::FAIL.severity(), SlangResult::SEVERITY_ERROR);
+        assert_eq!(
+            SlangResult::FAIL.facility(),
+            SlangResult::FACILITY_WIN_GENERAL
+        );
+        assert_eq!(SlangResult::FAIL.code(), 0x4005);
+
+        assert_eq!(
+            SlangResult::INVALID_ARG.facility(),
+            SlangResult::FACILITY_WIN_API
+        );
+        assert_eq!(SlangResult::INVALID_ARG.code(), 0x57);
+        assert_eq!(
+            SlangResult::NOT_FOUND.facility(),
+            SlangResult::FACILITY_CORE
+        );
+        assert_eq!(SlangResult::NOT_FOUND.code(), 5);
+
+        // Success codes other than `OK`.
+        let success = SlangResult(0x0201_0001);
+        assert!(success.is_ok());
+        assert_eq!(success.severity(), SlangResult::SEVERITY_SUCCESS);
+        assert_eq!(success.facility(), SlangResult::FACILITY_INTERNAL);
+        assert_eq!(success.code(), 1);
+    }
+
+    #[test]
+    fn slang_result_display() {
+        assert_eq!(SlangResult::OK.to_string(), "SLANG_OK");
+        assert_eq!(SlangResult::FAIL.to_string(), "E_FAIL");
+        assert_eq!(SlangResult::NOT_FOUND.to_string(), "SLANG_E_NOT_FOUND");
+        assert_eq!(
+            SlangResult(0x8200_0012).to_string(),
+            "SLANG_FACILITY_CORE:0x0012"
+        );
+        assert_eq!(SlangResult(0x8300_0001).to_string(), "0x0300:0x0001");
+        assert_eq!(
+            format!("{:?}", SlangResult::FAIL),
+            "SlangResult(0x80004005)"
+        );
+    }
+
+    #[test]
+    fn error_chain_shows_each_message_once() {
+        let compile = CompileError::new(
+            SlangResult::FAIL,
+            "shader.slang(2): error 30015: undefined identifier 'x'\n".to_string(),
+        );
+        let error = Error::Permutation {
+            defines: DefineSet::from_pairs(&[("A", "1")]),
+            error: Box::new(Error::Compile(compile)),
+            diagnostics: String::new(),
+        };
+
+        let mut messages = vec![error.to_string()];
+        let mut source = error.source();
+        while let Some(error) = source {
+            messages.push(error.to_string());
+            source = error.source();
+        }
+        assert_eq!(
+            messages,
+            [
+                "permutation 'A=1' failed to compile",
+                "shader.slang(2): error 30015: undefined identifier 'x'",
+            ]
+        );
+
+        assert_eq!(Error::Slang(SlangResult::FAIL).to_string(), "E_FAIL");
+        assert!(Error::Slang(SlangResult::FAIL).source().is_none());
+    }
+
+    #[test]
+    fn specialization_error_from_diagnostics() {
+        let diagnostics = Diagnostic::parse_all(
+            "shader.slang(4): warning 15205: unused variable 'x'\n\
+             shader.slang(9): error 38029: type argument 'Foo' does not conform to the required interface 'IBar'\n",
+        );
+        assert_eq!(
+            SpecializationError::from_diagnostics(&diagnostics),
+            Some(SpecializationError::ConstraintNotSatisfied {
+                argument: "Foo".to_string(),
+                interface: "IBar".to_string(),
+                message: "type argument 'Foo' does not conform to the required interface 'IBar'"
+                    .to_string(),
+            })
+        );
+
+        // The code has to be the diagnostic's own, not just appear in the text.
+        let diagnostics = Diagnostic::parse_all(
+            "shader.slang(2): error 30015: undefined identifier 'error 38029'\n",
+        );
+        assert_eq!(SpecializationError::from_diagnostics(&diagnostics), None);
+        assert_eq!(SpecializationError::from_diagnostics(&[]), None);
+
+        // Without two quoted names the diagnostic is left for `Error::Compile`.
+        for message in &[
+            "type argument does not conform to the required interface",
+            "type argument 'Foo' does not conform to the required interface",
+            "type argument 'Foo' does not conform to the required interface 'IBar",
+        ] {
+            let diagnostics =
+                Diagnostic::parse_all(&format!("shader.slang(9): error 38029: {}\n", message));
+            assert_eq!(diagnostics[0].code, Some(38029));
+            assert_eq!(SpecializationError::from_diagnostics(&diagnostics), None);
         }
     }
 }
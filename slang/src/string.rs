@@ -0,0 +1,99 @@
+use crate::{Error, Profile, Result};
+use std::borrow::Cow;
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Strings that can be passed to Slang: `str`, `String`, `Path`, `PathBuf`, `OsStr`, `OsString`, `CStr` and
+/// `CString`, or references to them.
+///
+/// Conversion fails with `Error::InteriorNul` if the string contains a NUL byte, or with `Error::NonUnicodePath`
+/// for paths that can't be represented as UTF-8 on platforms where Slang expects UTF-8.
+pub trait SlangStr {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>>;
+}
+
+impl<T: SlangStr + ?Sized> SlangStr for &T {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        (**self).to_slang_str()
+    }
+}
+
+impl SlangStr for CStr {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        Ok(Cow::Borrowed(self))
+    }
+}
+
+impl SlangStr for CString {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        Ok(Cow::Borrowed(self.as_c_str()))
+    }
+}
+
+impl SlangStr for str {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        Ok(Cow::Owned(CString::new(self).map_err(Error::InteriorNul)?))
+    }
+}
+
+impl SlangStr for Profile {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        Ok(Cow::Owned(
+            CString::new(self.to_string()).map_err(Error::InteriorNul)?,
+        ))
+    }
+}
+
+impl SlangStr for String {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        self.as_str().to_slang_str()
+    }
+}
+
+impl SlangStr for OsStr {
+    #[cfg(unix)]
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        use std::os::unix::ffi::OsStrExt;
+        Ok(Cow::Owned(
+            CString::new(self.as_bytes()).map_err(Error::InteriorNul)?,
+        ))
+    }
+
+    #[cfg(not(unix))]
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        self.to_str()
+            .ok_or_else(|| Error::NonUnicodePath(PathBuf::from(self)))?
+            .to_slang_str()
+    }
+}
+
+impl SlangStr for OsString {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        self.as_os_str().to_slang_str()
+    }
+}
+
+impl SlangStr for Path {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        self.as_os_str().to_slang_str()
+    }
+}
+
+impl SlangStr for PathBuf {
+    fn to_slang_str(&self) -> Result<Cow<'_, CStr>> {
+        self.as_os_str().to_slang_str()
+    }
+}
+
+/// Converts a path returned by Slang, e.g. from `CompileRequest::get_dependency_file_path`.
+#[cfg(unix)]
+pub(crate) fn c_str_to_path(path: &CStr) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(OsStr::from_bytes(path.to_bytes()))
+}
+
+/// Converts a path returned by Slang, e.g. from `CompileRequest::get_dependency_file_path`.
+#[cfg(not(unix))]
+pub(crate) fn c_str_to_path(path: &CStr) -> PathBuf {
+    PathBuf::from(path.to_string_lossy().into_owned())
+}
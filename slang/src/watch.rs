@@ -0,0 +1,228 @@
+//! Recompiling shaders when the files they depend on change.
+//!
+//! ```no_run
+//! use slang::watch::ShaderWatcher;
+//! use slang::*;
+//! use std::time::Duration;
+//!
+//! let mut builder = CompileRequestBuilder::new();
+//! // ... targets, translation units and entry points ...
+//!
+//! let (mut watcher, reloads) = ShaderWatcher::new();
+//! let program = watcher.add(builder);
+//! let thread = watcher.spawn(Duration::from_millis(250));
+//!
+//! for reload in reloads.iter().take(10) {
+//!     match reload.output {
+//!         Ok(output) => { /* swap in the new code */ }
+//!         Err(_) => eprintln!("{}", reload.diagnostics),
+//!     }
+//! }
+//! thread.stop();
+//! ```
+
+use crate::string::c_str_to_path;
+use crate::{CompileOutput, CompileRequestBuilder, Result, Session};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+/// Identifies a program added with `ShaderWatcher::add`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ProgramId(usize);
+
+/// Sent whenever a program is compiled, including the first time.
+#[derive(Debug)]
+pub struct Reload {
+    pub program: ProgramId,
+    pub output: Result<CompileOutput>,
+    /// Everything Slang reported, including errors when `output` is an error.
+    pub diagnostics: String,
+}
+
+struct Program {
+    builder: CompileRequestBuilder,
+    /// The modification time of each dependency from just before it was last compiled. `None` for files that
+    /// couldn't be read, e.g. the in-memory paths given to `TranslationUnitDesc::source_string`.
+    dependencies: BTreeMap<PathBuf, Option<SystemTime>>,
+}
+
+/// Compiles programs and recompiles them when their dependency files are modified, by comparing modification
+/// times each time it's polled.
+pub struct ShaderWatcher {
+    session: Session,
+    programs: Vec<Program>,
+    sender: Sender<Reload>,
+    /// Set when a send fails because the receiver was dropped.
+    disconnected: bool,
+}
+
+impl ShaderWatcher {
+    /// The receiver gets a `Reload` for every compile.
+    pub fn new() -> (Self, Receiver<Reload>) {
+        let (sender, receiver) = mpsc::channel();
+        let watcher = Self {
+            session: Session::new(),
+            programs: Vec::new(),
+            sender,
+            disconnected: false,
+        };
+        (watcher, receiver)
+    }
+
+    /// Compiles `builder` right away and watches the files it depended on, starting with its
+    /// `TranslationUnitDesc::source_file`s so a program that fails to compile the first time is still recompiled.
+    ///
+    /// Translation units given with `source_string` or `source_bytes` are compiled from memory every time, so
+    /// editing a file at the same path recompiles the program without changing it. Use `source_file` for sources
+    /// that should be reloaded.
+    pub fn add(&mut self, builder: CompileRequestBuilder) -> ProgramId {
+        let id = ProgramId(self.programs.len());
+        let dependencies = builder
+            .source_files()
+            .map(|path| (path.to_path_buf(), None))
+            .collect();
+        self.programs.push(Program {
+            builder,
+            dependencies,
+        });
+        self.compile(id);
+        id
+    }
+
+    /// Recompiles every program with a dependency that changed since it was last compiled, and returns how many
+    /// were recompiled.
+    pub fn poll(&mut self) -> usize {
+        let changed = self
+            .programs
+            .iter()
+            .enumerate()
+            .filter(|(_, program)| {
+                program
+                    .dependencies
+                    .iter()
+                    .any(|(path, modified)| modified_time(path) != *modified)
+            })
+            .map(|(index, _)| ProgramId(index))
+            .collect::<Vec<_>>();
+
+        for &id in &changed {
+            self.compile(id);
+        }
+        changed.len()
+    }
+
+    /// Polls every `interval` on a new thread until the returned `WatchThread` is stopped or dropped, or a reload
+    /// can't be sent because the receiver was dropped.
+    pub fn spawn(mut self, interval: Duration) -> WatchThread {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let handle = thread::spawn(move || loop {
+            // Unparked early by `WatchThread::stop`.
+            thread::park_timeout(interval);
+            if thread_stop.load(Ordering::Relaxed) || self.disconnected {
+                break;
+            }
+            self.poll();
+        });
+
+        WatchThread {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    fn compile(&mut self, id: ProgramId) {
+        let program = &mut self.programs[id.0];
+
+        // Modification times are read before compiling, so a file saved during the compile is seen as changed at
+        // the next poll. Files that weren't watched yet count as changed if they're newer than the compile.
+        let started = SystemTime::now();
+        let previous = program
+            .dependencies
+            .keys()
+            .map(|path| (path.clone(), modified_time(path)))
+            .collect::<BTreeMap<_, _>>();
+
+        let (output, diagnostics, dependencies) = match program.builder.build(&self.session) {
+            Ok(mut request) => {
+                let output = program.builder.compile_request(&mut request);
+                let diagnostics = request
+                    .get_diagnostic_output()
+                    .to_string_lossy()
+                    .into_owned();
+                // Failed compiles still report the files they read before failing.
+                let dependencies = (0..request.get_dependency_file_count())
+                    .map(|index| c_str_to_path(request.get_dependency_file_path(index)))
+                    .collect::<Vec<_>>();
+                (output, diagnostics, dependencies)
+            }
+            Err(error) => (Err(error), String::new(), Vec::new()),
+        };
+
+        // If nothing was read, e.g. because of a syntax error in the first file, keep watching the old files so
+        // fixing the error triggers a recompile.
+        if dependencies.is_empty() {
+            program.dependencies = previous;
+        } else {
+            program.dependencies = dependencies
+                .into_iter()
+                .map(|path| {
+                    let modified = match previous.get(&path) {
+                        Some(&modified) => modified,
+                        None => modified_time(&path).filter(|&modified| modified < started),
+                    };
+                    (path, modified)
+                })
+                .collect();
+        }
+
+        let reload = Reload {
+            program: id,
+            output,
+            diagnostics,
+        };
+        if self.sender.send(reload).is_err() {
+            self.disconnected = true;
+        }
+    }
+}
+
+/// The thread started by `ShaderWatcher::spawn`, which is stopped when this is dropped.
+pub struct WatchThread {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WatchThread {
+    /// Stops polling and waits for the thread to exit, which may take until the end of a compile.
+    pub fn stop(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            handle.thread().unpark();
+            // A panic on the thread has already been reported.
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for WatchThread {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
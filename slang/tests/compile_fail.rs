@@ -0,0 +1,6 @@
+/// Checks that the borrow checker rejects using Slang objects and their outputs after they've been freed.
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,60 @@
+use slang::*;
+
+const SOURCE: &str = "
+RWStructuredBuffer<float> buffer;
+
+[numthreads(64, 1, 1)]
+void main(uint3 id : SV_DispatchThreadID) {
+    buffer[id.x] = buffer[id.x] * 2.0;
+}
+";
+
+const OP_SOURCE: u32 = 3;
+const OP_STRING: u32 = 7;
+const OP_LINE: u32 = 8;
+
+fn compile_spirv(level: DebugInfoLevel) -> Vec<u8> {
+    let mut builder = CompileRequestBuilder::new();
+    let target = builder.add_target(TargetDesc::new(CompileTarget::Spirv).profile("glsl_450"));
+    let unit = builder.add_translation_unit(
+        TranslationUnitDesc::new(SourceLanguage::Slang, "debug_info")
+            .source_string("debug_info.slang", SOURCE),
+    );
+    let entry_point = builder.add_entry_point(unit, "main", Stage::Compute);
+    builder.debug_info_level(level);
+
+    let output = builder.compile(&Session::new()).unwrap();
+    output.code(entry_point, target).unwrap().to_vec()
+}
+
+/// The opcode of every instruction after the 5 word header. Each instruction starts with its word count in the
+/// high 16 bits and its opcode in the low 16 bits.
+fn opcodes(spirv: &[u8]) -> Vec<u32> {
+    let words = spirv
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        .collect::<Vec<_>>();
+
+    let mut opcodes = Vec::new();
+    let mut index = 5;
+    while index < words.len() {
+        let word_count = (words[index] >> 16) as usize;
+        opcodes.push(words[index] & 0xffff);
+        index += word_count.max(1);
+    }
+    opcodes
+}
+
+#[test]
+fn debug_info_level_changes_spirv() {
+    let without = compile_spirv(DebugInfoLevel::None);
+    let with = compile_spirv(DebugInfoLevel::Maximal);
+    assert_ne!(without, with);
+
+    let without = opcodes(&without);
+    let with = opcodes(&with);
+    assert!(!without.contains(&OP_LINE));
+    assert!(with
+        .iter()
+        .any(|opcode| [OP_LINE, OP_STRING, OP_SOURCE].contains(opcode)));
+}
@@ -0,0 +1,225 @@
+//! Checks that every `SLANG_*` constant in each enum family of `slang.h` has a safe counterpart, by reading the same
+//! bindings `build.rs` generates the enums from.
+//!
+//! `build.rs` only generates the families it's told about, so `families_are_all_accounted_for` checks its list
+//! against every anonymous enum in the bindings. A new family in `slang.h` fails it until it's added to `build.rs`
+//! and `FAMILIES` below, or to `UNGENERATED`.
+
+use slang::*;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+const BINDINGS: &str = include_str!(env!("SLANG_BINDINGS"));
+
+/// `(name, type, value)` for every `pub const` in the bindings.
+fn constants() -> Vec<(&'static str, &'static str, i64)> {
+    BINDINGS
+        .match_indices("pub const ")
+        .filter_map(|(start, _)| {
+            let rest = &BINDINGS[start + "pub const ".len()..];
+            let declaration = &rest[..rest.find(';')?];
+            let colon = declaration.find(':')?;
+            let equals = declaration.find('=')?;
+            let value = declaration[equals + 1..].replace(char::is_whitespace, "");
+            Some((
+                declaration[..colon].trim(),
+                declaration[colon + 1..equals].trim(),
+                value.parse().ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// The constants sharing `anchor`'s anonymous enum type, without the `_COUNT`/`_COUNT_OF` markers.
+fn family(anchor: &str) -> Vec<(&'static str, i64)> {
+    let constants = constants();
+    let ty = constants
+        .iter()
+        .find(|(name, _, _)| *name == anchor)
+        .unwrap_or_else(|| panic!("`{}` isn't in the bindings", anchor))
+        .1;
+    assert!(
+        ty.starts_with("_bindgen_ty_"),
+        "`{}` isn't in an anonymous enum",
+        anchor
+    );
+
+    constants
+        .iter()
+        .filter(|(name, t, _)| {
+            *t == ty && !name.ends_with("_COUNT") && !name.ends_with("_COUNT_OF")
+        })
+        .map(|&(name, _, value)| (name, value))
+        .collect()
+}
+
+/// A member of each family `build.rs` generates an enum or flags for.
+const FAMILIES: &[&str] = &[
+    "SLANG_SEVERITY_ERROR",
+    "SLANG_TEXTURE",
+    "SLANG_HLSL",
+    "SLANG_CONTAINER_FORMAT_NONE",
+    "SLANG_PASS_THROUGH_DXC",
+    "SLANG_FLOATING_POINT_MODE_FAST",
+    "SLANG_LINE_DIRECTIVE_MODE_STANDARD",
+    "SLANG_SOURCE_LANGUAGE_SLANG",
+    "SLANG_MATRIX_LAYOUT_ROW_MAJOR",
+    "SLANG_STAGE_VERTEX",
+    "SLANG_DEBUG_INFO_LEVEL_STANDARD",
+    "SLANG_OPTIMIZATION_LEVEL_DEFAULT",
+    "SLANG_PARAMETER_CATEGORY_UNIFORM",
+    "SLANG_TYPE_KIND_STRUCT",
+    "SLANG_COMPILE_FLAG_NO_CODEGEN",
+    "SLANG_TARGET_FLAG_PARAMETER_BLOCKS_USE_REGISTER_SPACES",
+];
+
+/// Prefixes of families that intentionally have no safe counterpart, e.g. because the C API using them isn't
+/// wrapped.
+const UNGENERATED: &[&str] = &[
+    "SLANG_PROFILE_",
+    "SLANG_WRITER_CHANNEL_",
+    "SLANG_WRITER_MODE_",
+    "SLANG_SCALAR_TYPE_",
+    "SLANG_RESOURCE_",
+    "SLANG_LAYOUT_RULES_",
+    "SLANG_MODIFIER_",
+];
+
+fn check_enum<T>(anchor: &str, all: &[T], name: fn(T) -> &'static str)
+where
+    T: Copy + Debug + PartialEq + TryFrom<i32> + FromStr,
+{
+    for (constant, value) in family(anchor) {
+        assert!(
+            T::try_from(value as i32).is_ok(),
+            "`{}` ({}) has no variant",
+            constant,
+            value
+        );
+    }
+    for &variant in all {
+        assert!(
+            T::from_str(name(variant)).ok() == Some(variant),
+            "{:?} doesn't round-trip through its name `{}`",
+            variant,
+            name(variant)
+        );
+    }
+}
+
+#[test]
+fn families_are_all_accounted_for() {
+    let constants = constants();
+    let type_of = |anchor: &str| {
+        constants
+            .iter()
+            .find(|(name, _, _)| *name == anchor)
+            .unwrap_or_else(|| panic!("`{}` isn't in the bindings", anchor))
+            .1
+    };
+    let generated = FAMILIES
+        .iter()
+        .map(|anchor| type_of(anchor))
+        .collect::<Vec<_>>();
+
+    let mut types = constants
+        .iter()
+        .filter(|(name, ty, _)| name.starts_with("SLANG_") && ty.starts_with("_bindgen_ty_"))
+        .map(|&(_, ty, _)| ty)
+        .collect::<Vec<_>>();
+    types.sort_unstable();
+    types.dedup();
+
+    for ty in types {
+        let members = constants
+            .iter()
+            .filter(|&&(_, t, _)| t == ty)
+            .map(|&(name, _, _)| name)
+            .collect::<Vec<_>>();
+        let ungenerated = members
+            .iter()
+            .any(|name| UNGENERATED.iter().any(|prefix| name.starts_with(prefix)));
+        assert!(
+            generated.contains(&ty) || ungenerated,
+            "the family of {:?} has no safe counterpart, add it to `build.rs`",
+            members
+        );
+    }
+}
+
+#[test]
+fn enums_cover_every_constant() {
+    check_enum("SLANG_SEVERITY_ERROR", Severity::ALL, Severity::name);
+    check_enum(
+        "SLANG_TEXTURE",
+        BindableResourceType::ALL,
+        BindableResourceType::name,
+    );
+    check_enum("SLANG_HLSL", CompileTarget::ALL, CompileTarget::name);
+    check_enum(
+        "SLANG_CONTAINER_FORMAT_NONE",
+        ContainerFormat::ALL,
+        ContainerFormat::name,
+    );
+    check_enum(
+        "SLANG_PASS_THROUGH_DXC",
+        PassThrough::ALL,
+        PassThrough::name,
+    );
+    check_enum(
+        "SLANG_FLOATING_POINT_MODE_FAST",
+        FloatingPointMode::ALL,
+        FloatingPointMode::name,
+    );
+    check_enum(
+        "SLANG_LINE_DIRECTIVE_MODE_STANDARD",
+        LineDirectiveMode::ALL,
+        LineDirectiveMode::name,
+    );
+    check_enum(
+        "SLANG_SOURCE_LANGUAGE_SLANG",
+        SourceLanguage::ALL,
+        SourceLanguage::name,
+    );
+    check_enum(
+        "SLANG_MATRIX_LAYOUT_ROW_MAJOR",
+        MatrixLayoutMode::ALL,
+        MatrixLayoutMode::name,
+    );
+    check_enum("SLANG_STAGE_VERTEX", Stage::ALL, Stage::name);
+    check_enum(
+        "SLANG_DEBUG_INFO_LEVEL_STANDARD",
+        DebugInfoLevel::ALL,
+        DebugInfoLevel::name,
+    );
+    check_enum(
+        "SLANG_OPTIMIZATION_LEVEL_DEFAULT",
+        OptimizationLevel::ALL,
+        OptimizationLevel::name,
+    );
+    check_enum(
+        "SLANG_PARAMETER_CATEGORY_UNIFORM",
+        ParameterCategory::ALL,
+        ParameterCategory::name,
+    );
+    check_enum("SLANG_TYPE_KIND_STRUCT", TypeKind::ALL, TypeKind::name);
+}
+
+#[test]
+fn flags_cover_every_constant() {
+    for (constant, value) in family("SLANG_COMPILE_FLAG_NO_CODEGEN") {
+        assert!(
+            CompileFlags::from_bits(value as i32).is_some(),
+            "`{}` has no flag",
+            constant
+        );
+    }
+    for (constant, value) in family("SLANG_TARGET_FLAG_PARAMETER_BLOCKS_USE_REGISTER_SPACES") {
+        assert!(
+            TargetFlags::from_bits(value as i32).is_some(),
+            "`{}` has no flag",
+            constant
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use slang::{CompileTarget, Error, Session, SourceLanguage, Stage, TargetFlags};
+
+/// Indices from one request are rejected by another, even when both come from the same session.
+#[test]
+fn indices_from_another_request_are_rejected() {
+    let session = Session::new();
+
+    let first = session.create_compile_request();
+    let first_target = first.add_code_gen_target(CompileTarget::Spirv);
+    let first_unit = first
+        .add_translation_unit(SourceLanguage::Slang, "first")
+        .unwrap();
+    first.add_code_gen_target(CompileTarget::Hlsl);
+    first
+        .add_entry_point(first_unit, "main", Stage::Compute)
+        .unwrap();
+    let first_entry_point = first
+        .add_entry_point(first_unit, "other", Stage::Compute)
+        .unwrap();
+
+    let second = session.create_compile_request();
+    let second_target = second.add_code_gen_target(CompileTarget::Spirv);
+    let second_unit = second
+        .add_translation_unit(SourceLanguage::Slang, "second")
+        .unwrap();
+    let second_entry_point = second
+        .add_entry_point(second_unit, "main", Stage::Compute)
+        .unwrap();
+
+    assert!(matches!(
+        second.get_entry_point_code(first_entry_point),
+        Err(Error::UnknownIndex { .. })
+    ));
+    assert!(matches!(
+        second.get_entry_point_code_blob(first_entry_point, second_target),
+        Err(Error::UnknownIndex { .. })
+    ));
+    assert_eq!(second.code_gen_targets(), vec![second_target]);
+    assert_eq!(first.code_gen_targets()[0], first_target);
+
+    // The same values as the second request's own indices, but still from the first request.
+    assert_eq!(first_target.get(), second_target.get());
+    assert_eq!(first_unit.get(), second_unit.get());
+    assert!(matches!(
+        second.set_target_flags(first_target, TargetFlags::empty()),
+        Err(Error::UnknownIndex { .. })
+    ));
+    assert!(matches!(
+        second.add_entry_point(first_unit, "other", Stage::Compute),
+        Err(Error::UnknownIndex { .. })
+    ));
+    assert!(matches!(
+        second.get_entry_point_code_blob(second_entry_point, first_target),
+        Err(Error::UnknownIndex { .. })
+    ));
+}
+
+/// `set_code_gen_target` leaves a single target at index 0.
+#[test]
+fn set_code_gen_target_replaces_targets() {
+    let session = Session::new();
+    let request = session.create_compile_request();
+    request.add_code_gen_target(CompileTarget::Spirv);
+    let hlsl = request.add_code_gen_target(CompileTarget::Hlsl);
+
+    let glsl = request.set_code_gen_target(CompileTarget::Glsl);
+    assert_eq!(glsl.get(), 0);
+    assert_eq!(request.code_gen_targets(), vec![glsl]);
+    request
+        .set_target_flags(glsl, TargetFlags::empty())
+        .unwrap();
+    let profile = session.find_profile("glsl_450").unwrap().unwrap();
+    request.set_target_profile(glsl, profile).unwrap();
+
+    assert!(matches!(
+        request.set_target_flags(hlsl, TargetFlags::empty()),
+        Err(Error::UnknownIndex { .. })
+    ));
+}
@@ -0,0 +1,19 @@
+use slang::{SourceLanguage, Stage};
+
+fn main() {
+    let session = slang::Session::new();
+    let mut request = session.create_compile_request();
+    let translation_unit = request
+        .add_translation_unit(SourceLanguage::Slang, "shader")
+        .unwrap();
+    let entry_point = request
+        .add_entry_point(translation_unit, "main", Stage::Compute)
+        .unwrap();
+    request.compile().unwrap();
+
+    let code = request.get_entry_point_code(entry_point).unwrap();
+
+    drop(request);
+
+    println!("{:?}", code);
+}
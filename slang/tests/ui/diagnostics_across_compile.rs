@@ -0,0 +1,9 @@
+fn main() {
+    let session = slang::Session::new();
+    let mut request = session.create_compile_request();
+    let diagnostics = request.get_diagnostic_output();
+
+    let _ = request.compile();
+
+    println!("{:?}", diagnostics);
+}
@@ -0,0 +1,9 @@
+fn main() {
+    let session = slang::Session::new();
+    let request = session.create_compile_request();
+    let diagnostics = request.get_diagnostic_output();
+
+    drop(request);
+
+    println!("{:?}", diagnostics);
+}
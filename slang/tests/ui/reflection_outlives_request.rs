@@ -0,0 +1,9 @@
+fn main() {
+    let session = slang::Session::new();
+    let reflection = {
+        let request = session.create_compile_request();
+        request.get_reflection()
+    };
+
+    println!("{:?}", reflection);
+}
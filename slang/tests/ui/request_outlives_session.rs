@@ -0,0 +1,8 @@
+fn main() {
+    let request = {
+        let session = slang::Session::new();
+        session.create_compile_request()
+    };
+
+    drop(request);
+}
@@ -0,0 +1,406 @@
+//! A `slangc`-style command line compiler. Run with `-help` for usage.
+
+#![warn(clippy::all)]
+
+use slang::{
+    CompileRequestBuilder, CompileTarget, ContainerFormat, DebugInfoLevel, Diagnostic,
+    FloatingPointMode, LineDirectiveMode, MatrixLayoutMode, OptimizationLevel, Session,
+    SourceLanguage, Stage, TargetDesc, TranslationUnitDesc,
+};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+const USAGE: &str = "\
+usage: slangc [options] <file>...
+
+Each input file is its own translation unit. The source language is taken from the extension
+(.slang, .hlsl or .glsl).
+
+options:
+  -target <target>            Add a target: hlsl, glsl, glsl-vulkan, glsl-vulkan-one-desc, spirv,
+                              spirv-asm, dxbc, dxbc-asm, dxil, dxil-asm
+  -profile <profile>          Profile for the preceding -target, or for all targets if given first,
+                              e.g. sm_5_0 or glsl_450
+  -entry <name>               Add an entry point in the preceding input file (or the first one)
+  -stage <stage>              Stage of the preceding -entry: vertex, hull, domain, geometry,
+                              fragment (or pixel), compute, raygeneration, intersection, anyhit,
+                              closesthit, miss, callable
+  -D<name>[=<value>]          Define a preprocessor macro, also accepted as -D <name>[=<value>]
+  -I<path>                    Add an include search path, also accepted as -I <path>
+  -matrix-layout-row-major    Use row major matrix layout
+  -matrix-layout-column-major Use column major matrix layout
+  -fp-mode <mode>             Floating point mode: default, fast, precise
+  -line-directive-mode <mode> Line directives in text output: default, none, standard, glsl
+  -g, -g<level>               Debug info level: 0 (none), 1 (minimal), 2 (standard), 3 (maximal).
+                              -g alone means -g2
+  -O<level>                   Optimization level: 0 (none), 1 (default), 2 (high), 3 (maximal)
+  -container-format <format>  Output container: none, slang-module. With slang-module, targets and
+                              entry points are optional and the module is written to -o, or
+                              module.slang-module in the output directory
+  -r <module>                 Reference a Slang module written with -container-format slang-module
+  -o <path>                   Output file, when there's one entry point and one target
+  -output-dir <dir>           Directory for outputs named <entry>.<extension> (default: .)
+  -capabilities               Print which targets and downstream compilers are available
+  -help                       Print this message
+
+exit codes: 0 on success, 1 if compiling or writing outputs failed, 2 for invalid arguments";
+
+struct Target {
+    target: CompileTarget,
+    profile: Option<String>,
+}
+
+struct EntryPoint {
+    /// Index into `Options::files`.
+    file: usize,
+    name: String,
+    stage: Option<Stage>,
+}
+
+#[derive(Default)]
+struct Options {
+    files: Vec<PathBuf>,
+    targets: Vec<Target>,
+    default_profile: Option<String>,
+    entry_points: Vec<EntryPoint>,
+    defines: Vec<(String, String)>,
+    search_paths: Vec<PathBuf>,
+    matrix_layout_mode: Option<MatrixLayoutMode>,
+    floating_point_mode: Option<FloatingPointMode>,
+    line_directive_mode: Option<LineDirectiveMode>,
+    debug_info_level: Option<DebugInfoLevel>,
+    optimization_level: Option<OptimizationLevel>,
+    container_format: Option<ContainerFormat>,
+    library_references: Vec<PathBuf>,
+    output: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+}
+
+impl Options {
+    /// A Slang module can be written on its own, e.g. for a shared library, so it doesn't need targets or entry
+    /// points.
+    fn is_module(&self) -> bool {
+        self.container_format == Some(ContainerFormat::ContainerFormatSlangModule)
+    }
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args
+        .iter()
+        .any(|arg| arg == "-help" || arg == "-h" || arg == "--help")
+    {
+        println!("{}", USAGE);
+        return;
+    }
+    if args.iter().any(|arg| arg == "-capabilities") {
+        print!("{}", Session::new().capabilities());
+        return;
+    }
+
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("slangc: {}", message);
+            eprintln!("run `slangc -help` for usage");
+            process::exit(2);
+        }
+    };
+
+    if let Err(message) = run(&options) {
+        eprintln!("slangc: {}", message);
+        process::exit(1);
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} expects a value", name))
+        };
+
+        match arg.as_str() {
+            "-target" => {
+                let name = value("-target")?;
+                let target = parse_enum(&name)?;
+                if extension(target).is_none() {
+                    return Err(format!("'{}' isn't a target slangc can write", name));
+                }
+                options.targets.push(Target {
+                    target,
+                    profile: None,
+                });
+            }
+            "-profile" => {
+                let profile = value("-profile")?;
+                match options.targets.last_mut() {
+                    Some(target) => target.profile = Some(profile),
+                    None => options.default_profile = Some(profile),
+                }
+            }
+            "-entry" => {
+                let name = value("-entry")?;
+                options.entry_points.push(EntryPoint {
+                    file: options.files.len().saturating_sub(1),
+                    name,
+                    stage: None,
+                });
+            }
+            "-stage" => {
+                let name = value("-stage")?;
+                let stage = parse_enum(&name)?;
+                if stage == Stage::None {
+                    return Err(format!("'{}' isn't a stage an entry point can have", name));
+                }
+                options
+                    .entry_points
+                    .last_mut()
+                    .ok_or("-stage must follow an -entry")?
+                    .stage = Some(stage);
+            }
+            "-D" => options.defines.push(parse_define(&value("-D")?)),
+            "-I" => options.search_paths.push(value("-I")?.into()),
+            "-matrix-layout-row-major" => {
+                options.matrix_layout_mode = Some(MatrixLayoutMode::RowMajor)
+            }
+            "-matrix-layout-column-major" => {
+                options.matrix_layout_mode = Some(MatrixLayoutMode::ColumnMajor)
+            }
+            "-fp-mode" => {
+                options.floating_point_mode = Some(parse_enum(&value("-fp-mode")?)?);
+            }
+            "-line-directive-mode" => {
+                options.line_directive_mode = Some(parse_enum(&value("-line-directive-mode")?)?);
+            }
+            "-container-format" => {
+                options.container_format = Some(parse_enum(&value("-container-format")?)?);
+            }
+            "-g" | "-g2" => options.debug_info_level = Some(DebugInfoLevel::Standard),
+            "-g0" => options.debug_info_level = Some(DebugInfoLevel::None),
+            "-g1" => options.debug_info_level = Some(DebugInfoLevel::Minimal),
+            "-g3" => options.debug_info_level = Some(DebugInfoLevel::Maximal),
+            "-O0" => options.optimization_level = Some(OptimizationLevel::None),
+            "-O1" => options.optimization_level = Some(OptimizationLevel::Default),
+            "-O2" => options.optimization_level = Some(OptimizationLevel::High),
+            "-O3" => options.optimization_level = Some(OptimizationLevel::Maximal),
+            "-r" => options.library_references.push(value("-r")?.into()),
+            "-o" => options.output = Some(value("-o")?.into()),
+            "-output-dir" => options.output_dir = Some(value("-output-dir")?.into()),
+            arg if arg.starts_with("-D") => options.defines.push(parse_define(&arg[2..])),
+            arg if arg.starts_with("-I") => options.search_paths.push(arg[2..].into()),
+            arg if arg.starts_with('-') => return Err(format!("unknown option '{}'", arg)),
+            file => options.files.push(file.into()),
+        }
+    }
+
+    if options.files.is_empty() {
+        return Err("no input files".to_string());
+    }
+    let is_module = options.is_module();
+    if options.targets.is_empty() && !is_module {
+        return Err("no targets, add one with -target".to_string());
+    }
+    if options.entry_points.is_empty() && !is_module {
+        return Err("no entry points, add one with -entry".to_string());
+    }
+    if let Some(entry_point) = options
+        .entry_points
+        .iter()
+        .find(|entry| entry.stage.is_none())
+    {
+        return Err(format!("entry point '{}' has no -stage", entry_point.name));
+    }
+    if options.output.is_some()
+        && !is_module
+        && options.entry_points.len() * options.targets.len() != 1
+    {
+        return Err(
+            "-o needs exactly one entry point and one target, use -output-dir instead".to_string(),
+        );
+    }
+
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let mut builder = CompileRequestBuilder::new();
+
+    for target in &options.targets {
+        let mut desc = TargetDesc::new(target.target);
+        if let Some(profile) = target.profile.as_ref().or(options.default_profile.as_ref()) {
+            desc = desc.profile(profile.clone());
+        }
+        if let Some(mode) = options.floating_point_mode {
+            desc = desc.floating_point_mode(mode);
+        }
+        builder.add_target(desc);
+    }
+
+    for path in &options.search_paths {
+        builder.search_path(path.clone());
+    }
+    for (key, value) in &options.defines {
+        builder.define(key.clone(), value.clone());
+    }
+    if let Some(mode) = options.matrix_layout_mode {
+        builder.matrix_layout_mode(mode);
+    }
+    if let Some(mode) = options.line_directive_mode {
+        builder.line_directive_mode(mode);
+    }
+    if let Some(level) = options.debug_info_level {
+        builder.debug_info_level(level);
+    }
+    if let Some(level) = options.optimization_level {
+        builder.optimization_level(level);
+    }
+    if let Some(format) = options.container_format {
+        builder.output_container_format(format);
+    }
+    for path in &options.library_references {
+        let module = fs::read(path)
+            .map_err(|error| format!("couldn't read {}: {}", path.display(), error))?;
+        builder.library_reference(module);
+    }
+
+    let mut units = Vec::with_capacity(options.files.len());
+    for file in &options.files {
+        units.push(
+            builder.add_translation_unit(
+                TranslationUnitDesc::new(source_language(file), file.to_string_lossy())
+                    .source_file(file),
+            ),
+        );
+    }
+
+    for entry_point in &options.entry_points {
+        builder.add_entry_point(
+            units[entry_point.file],
+            entry_point.name.clone(),
+            entry_point.stage.unwrap(),
+        );
+    }
+
+    let output = match builder.compile(&Session::new()) {
+        Ok(output) => {
+            print_diagnostics(
+                &Diagnostic::parse_all(&output.diagnostics),
+                &output.diagnostics,
+            );
+            output
+        }
+        Err(slang::Error::Compile(error)) => {
+            print_diagnostics(&error.diagnostics, &error.output);
+            return Err(format!("compilation failed ({})", error.result));
+        }
+        Err(error) => return Err(format!("compilation failed: {}", error)),
+    };
+
+    let output_dir = options
+        .output_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("."));
+    let is_module = options.is_module();
+    let mut written = Vec::new();
+
+    if is_module {
+        let path = options
+            .output
+            .clone()
+            .unwrap_or_else(|| output_dir.join("module.slang-module"));
+        write_output(path, &output.container, &mut written)?;
+    }
+
+    for (entry_point, entry_point_output) in options.entry_points.iter().zip(&output.entry_points) {
+        for (target, code) in options.targets.iter().zip(&entry_point_output.code) {
+            let path = match &options.output {
+                Some(path) if !is_module => path.clone(),
+                _ => output_dir.join(format!(
+                    "{}.{}",
+                    entry_point.name,
+                    extension(target.target).unwrap()
+                )),
+            };
+            write_output(path, code, &mut written)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints each diagnostic on its own line, or `output` as is if none of it could be parsed.
+fn print_diagnostics(diagnostics: &[Diagnostic], output: &str) {
+    if diagnostics.is_empty() {
+        eprint!("{}", output);
+    }
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+}
+
+/// Writes `data` to `path`, failing instead of overwriting a file written earlier in the same run.
+fn write_output(path: PathBuf, data: &[u8], written: &mut Vec<PathBuf>) -> Result<(), String> {
+    if written.contains(&path) {
+        return Err(format!(
+            "more than one output would be written to {}",
+            path.display()
+        ));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("couldn't create {}: {}", parent.display(), error))?;
+    }
+    fs::write(&path, data)
+        .map_err(|error| format!("couldn't write {}: {}", path.display(), error))?;
+    written.push(path);
+    Ok(())
+}
+
+/// `NAME=VALUE`, or just `NAME` for an empty value.
+fn parse_define(define: &str) -> (String, String) {
+    let mut parts = define.splitn(2, '=');
+    let name = parts.next().unwrap_or_default().to_string();
+    let value = parts.next().unwrap_or_default().to_string();
+    (name, value)
+}
+
+/// Targets, stages and modes use the names their `FromStr` implementations accept.
+fn parse_enum<T: FromStr<Err = slang::Error>>(name: &str) -> Result<T, String> {
+    name.parse()
+        .map_err(|error: slang::Error| error.to_string())
+}
+
+fn source_language(path: &Path) -> SourceLanguage {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("hlsl") => SourceLanguage::Hlsl,
+        Some("glsl") | Some("vert") | Some("frag") | Some("comp") => SourceLanguage::Glsl,
+        _ => SourceLanguage::Slang,
+    }
+}
+
+/// The extension outputs for `target` are written with, or `None` for targets slangc doesn't write.
+fn extension(target: CompileTarget) -> Option<&'static str> {
+    Some(match target {
+        CompileTarget::Hlsl => "hlsl",
+        CompileTarget::Glsl | CompileTarget::GlslVulkan | CompileTarget::GlslVulkanOneDesc => {
+            "glsl"
+        }
+        CompileTarget::Spirv => "spv",
+        CompileTarget::SpirvAsm => "spv.asm",
+        CompileTarget::Dxbc => "dxbc",
+        CompileTarget::DxbcAsm => "dxbc.asm",
+        CompileTarget::Dxil => "dxil",
+        CompileTarget::DxilAsm => "dxil.asm",
+        // `TargetUnknown`, `TargetNone`, and targets like executables that aren't shader code.
+        _ => return None,
+    })
+}